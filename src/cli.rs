@@ -0,0 +1,205 @@
+//! # Cli
+//!
+//! Command line subcommands of tuifeed
+
+mod digest;
+mod fetch;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use argh::FromArgs;
+
+use self::digest::DigestArgs;
+use self::fetch::FetchArgs;
+use crate::helpers::path as path_helpers;
+use crate::ui::History;
+
+/// tuifeed subcommands
+#[derive(FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    Digest(DigestArgs),
+    Fetch(FetchArgs),
+    History(HistoryArgs),
+    Profiles(ProfilesArgs),
+    Stats(StatsArgs),
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "history", description = "manage the read history")]
+pub struct HistoryArgs {
+    #[argh(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum HistoryCommand {
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "export",
+    description = "export the read history to a portable JSON file"
+)]
+struct ExportArgs {
+    #[argh(positional, description = "path of the file to write")]
+    path: PathBuf,
+}
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "import",
+    description = "merge a read history exported with `tuifeed history export`; the most recent read state of each article wins"
+)]
+struct ImportArgs {
+    #[argh(positional, description = "path of the exported file")]
+    path: PathBuf,
+}
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "profiles",
+    description = "list the existing profiles; the one in use is marked with `*`"
+)]
+pub struct ProfilesArgs {}
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "stats",
+    description = "show reading statistics for each source"
+)]
+pub struct StatsArgs {
+    #[argh(
+        option,
+        default = "StatsFormat::Table",
+        description = "output format: table or json; default table"
+    )]
+    format: StatsFormat,
+    #[argh(
+        option,
+        description = "report sources where nothing has been read for this amount of days; default 30"
+    )]
+    inactive_days: Option<u64>,
+}
+
+/// Output format of the statistics
+enum StatsFormat {
+    Json,
+    Table,
+}
+
+impl FromStr for StatsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "table" => Ok(Self::Table),
+            _ => Err(format!("unknown format \"{s}\"; expected table or json")),
+        }
+    }
+}
+
+impl Command {
+    /// Run the command for `profile`; `None` is the default profile.
+    /// `config_path` is the configuration file in use. Returns the exit code of the process
+    pub fn run(
+        self,
+        profile: Option<&str>,
+        config_path: Option<&Path>,
+    ) -> Result<ExitCode, Box<dyn std::error::Error>> {
+        match self {
+            Self::Digest(args) => args.run(profile, config_path),
+            Self::Fetch(args) => args.run(profile, config_path),
+            Self::History(args) => args.run(profile, config_path).map(|()| ExitCode::SUCCESS),
+            Self::Profiles(args) => args.run(profile).map(|()| ExitCode::SUCCESS),
+            Self::Stats(args) => args.run(profile, config_path).map(|()| ExitCode::SUCCESS),
+        }
+    }
+}
+
+impl HistoryArgs {
+    fn run(
+        self,
+        profile: Option<&str>,
+        config_path: Option<&Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = crate::init_config(config_path)?;
+        let mut history = History::load(&History::default_path(profile)?)?;
+        if let Some(warning) = history.take_warning() {
+            eprintln!("{warning}");
+        }
+        history.set_source_identities(config.source_identities());
+        match self.command {
+            HistoryCommand::Export(args) => {
+                history.save()?;
+                history.export(&args.path)?;
+                println!("History exported to {}", args.path.display());
+            }
+            HistoryCommand::Import(args) => {
+                let count = history.import(&args.path)?;
+                history.save()?;
+                println!("Merged read state of {count} articles");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ProfilesArgs {
+    fn run(self, profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let current = profile.unwrap_or(path_helpers::DEFAULT_PROFILE);
+        for name in path_helpers::list_profiles()? {
+            let marker = if name == current { '*' } else { ' ' };
+            println!("{marker} {name}");
+        }
+
+        Ok(())
+    }
+}
+
+impl StatsArgs {
+    fn run(
+        self,
+        profile: Option<&str>,
+        config_path: Option<&Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = crate::init_config(config_path)?;
+        let mut history = History::load(&History::default_path(profile)?)?;
+        history.set_source_identities(config.source_identities());
+        let sources: Vec<&String> = config.sources.keys().collect();
+        let stats = history.stats(
+            &sources,
+            self.inactive_days.unwrap_or(config.inactive_days()),
+        );
+        match self.format {
+            StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+            StatsFormat::Table => {
+                for line in stats.table() {
+                    println!("{line}");
+                }
+                let inactive: Vec<&str> = stats.inactive().map(|x| x.name.as_str()).collect();
+                if !inactive.is_empty() {
+                    println!();
+                    println!(
+                        "Nothing read in the last {} days: {}",
+                        stats.inactive_days,
+                        inactive.join(", ")
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,509 @@
+//! # Digest
+//!
+//! Export of the recent articles as a Markdown or HTML digest
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+
+use argh::FromArgs;
+use chrono::{DateTime, Local, TimeDelta};
+use lazy_regex::{Captures, Lazy, Regex};
+
+use super::fetch::{self, exit_code};
+use crate::config::Config;
+use crate::feed::Article;
+use crate::helpers::strings as str_helpers;
+use crate::ui::{FeedState, History, Kiosk, Retention};
+
+/// Marker of the template section repeated for each source
+const SOURCE_MARKER: &str = "<!-- source -->";
+/// Marker of the template section repeated for each article
+const ARTICLE_MARKER: &str = "<!-- article -->";
+/// Marker of the template section written after the sources
+const FOOTER_MARKER: &str = "<!-- footer -->";
+/// Default maximum length of the article summaries
+const DEFAULT_SUMMARY_LENGTH: usize = 280;
+/// Format of the dates in the digest
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+const MARKDOWN_TEMPLATE: &str = r#"# {title}
+
+{since} - {until}
+<!-- source -->
+
+## {source}
+<!-- article -->
+
+### [{title}]({url})
+
+*{date}* {authors}
+
+{summary}
+<!-- footer -->
+"#;
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>{title}</title>
+  </head>
+  <body>
+    <h1>{title}</h1>
+    <p>{since} - {until}</p>
+<!-- source -->
+    <h2>{source}</h2>
+<!-- article -->
+    <article>
+      <h3><a href="{url}">{title}</a></h3>
+      <p><small>{date} {authors}</small></p>
+      <p>{summary}</p>
+    </article>
+<!-- footer -->
+  </body>
+</html>
+"#;
+
+/// Matches a template placeholder, such as `{title}`
+static PLACEHOLDER_REGEX: Lazy<Regex> = lazy_regex!(r"\{([a-z]+)\}");
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "digest",
+    description = "render the recent articles as a Markdown or HTML digest, grouped by source",
+    note = "Exit codes are the same as for `tuifeed fetch`."
+)]
+pub struct DigestArgs {
+    #[argh(
+        option,
+        default = "DigestFormat::Markdown",
+        description = "output format: markdown or html; default markdown"
+    )]
+    format: DigestFormat,
+    #[argh(
+        option,
+        default = "Since(Duration::from_secs(7 * 24 * 60 * 60))",
+        description = "include the articles published in this period (e.g. 12h, 7d, 2w); default 7d"
+    )]
+    since: Since,
+    #[argh(
+        option,
+        description = "include only the source with this name; can be repeated"
+    )]
+    source: Vec<String>,
+    #[argh(
+        option,
+        description = "template file to use instead of the configured or default one"
+    )]
+    template: Option<PathBuf>,
+    #[argh(switch, description = "include only the unread articles")]
+    unread_only: bool,
+}
+
+/// Output format of the digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DigestFormat {
+    Html,
+    Markdown,
+}
+
+impl FromStr for DigestFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(format!("unknown format \"{s}\"; expected markdown or html")),
+        }
+    }
+}
+
+/// Period of time before now, such as `7d`
+#[derive(Debug, PartialEq, Eq)]
+struct Since(Duration);
+
+impl FromStr for Since {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error =
+            || format!("invalid period \"{s}\"; expected a number followed by m, h, d or w");
+        let unit = s.chars().last().ok_or_else(error)?;
+        let amount: u64 = s[..s.len() - unit.len_utf8()]
+            .parse()
+            .map_err(|_| error())?;
+        let seconds = match unit {
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(error()),
+        };
+        let seconds = amount
+            .checked_mul(seconds)
+            .ok_or_else(|| format!("period \"{s}\" is too long"))?;
+
+        Ok(Self(Duration::from_secs(seconds)))
+    }
+}
+
+/// Articles to render in a digest, grouped by source
+struct Digest<'a> {
+    format: DigestFormat,
+    since: DateTime<Local>,
+    until: DateTime<Local>,
+    summary_length: usize,
+    sources: Vec<(String, Vec<&'a Article>)>,
+}
+
+impl Digest<'_> {
+    /// Render the digest with `template`.
+    /// The template is split by the source, article and footer markers into sections; the text before the source marker is the header
+    fn render(&self, template: &str) -> String {
+        let (header, rest) = template.split_once(SOURCE_MARKER).unwrap_or((template, ""));
+        let (source, rest) = rest.split_once(ARTICLE_MARKER).unwrap_or((rest, ""));
+        let (article, footer) = rest.split_once(FOOTER_MARKER).unwrap_or((rest, ""));
+        let count = self
+            .sources
+            .iter()
+            .map(|(_, articles)| articles.len())
+            .sum::<usize>()
+            .to_string();
+        let since = self.since.format(DATE_FORMAT).to_string();
+        let until = self.until.format(DATE_FORMAT).to_string();
+        let digest = [
+            ("title", String::from("tuifeed digest")),
+            ("since", since),
+            ("until", until),
+            ("count", count),
+        ];
+
+        let mut output = self.fill(strip_marker_line(header), &digest);
+        for (name, articles) in &self.sources {
+            output.push_str(&self.fill(
+                strip_marker_line(source),
+                &[
+                    ("source", name.clone()),
+                    ("count", articles.len().to_string()),
+                ],
+            ));
+            for item in articles {
+                output.push_str(&self.fill(strip_marker_line(article), &self.article(name, item)));
+            }
+        }
+        output.push_str(&self.fill(strip_marker_line(footer), &digest));
+
+        output
+    }
+
+    /// Get the placeholder values of `article`
+    fn article(&self, source: &str, article: &Article) -> [(&'static str, String); 6] {
+        let summary = article
+            .summary
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let authors = if article.authors.is_empty() {
+            String::new()
+        } else {
+            format!("by {}", article.authors.join(", "))
+        };
+        [
+            ("source", source.to_string()),
+            (
+                "title",
+                article.title.clone().unwrap_or_else(|| article.url.clone()),
+            ),
+            ("url", safe_url(&article.url).to_string()),
+            (
+                "date",
+                article_date(article)
+                    .map(|x| x.format(DATE_FORMAT).to_string())
+                    .unwrap_or_default(),
+            ),
+            ("authors", authors),
+            (
+                "summary",
+                str_helpers::elide_string_at(&summary, self.summary_length.max(1)),
+            ),
+        ]
+    }
+
+    /// Replace the placeholders in `template` with `values`, escaped for the digest format.
+    /// Unknown placeholders are kept as they are
+    fn fill(&self, template: &str, values: &[(&str, String)]) -> String {
+        PLACEHOLDER_REGEX
+            .replace_all(template, |caps: &Captures| {
+                match values.iter().find(|(name, _)| *name == &caps[1]) {
+                    Some((_, value)) if self.format == DigestFormat::Html => escape_html(value),
+                    Some((_, value)) => escape_markdown(&value.replace(['\r', '\n'], " ")),
+                    None => caps[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+}
+
+impl DigestArgs {
+    pub(super) fn run(
+        self,
+        profile: Option<&str>,
+        config_path: Option<&Path>,
+    ) -> Result<ExitCode, Box<dyn std::error::Error>> {
+        let config = crate::init_config(config_path)?;
+        let template = self.template(&config)?;
+        let until = Local::now();
+        let since = TimeDelta::from_std(self.since.0)
+            .ok()
+            .and_then(|period| until.checked_sub_signed(period))
+            .ok_or("the period of --since is too long")?;
+        let sources = fetch::select_sources(&config, &self.source)?;
+        let mut history = History::load(&History::default_path(profile)?)?;
+        if let Some(warning) = history.take_warning() {
+            eprintln!("{warning}");
+        }
+        history.set_source_identities(config.source_identities());
+        history.set_retention(Retention::from(&config.history.clone().unwrap_or_default()));
+
+        let mut kiosk = Kiosk::default();
+        let mut failed = 0;
+        for (name, result) in fetch::fetch_sources(&config, &sources) {
+            let state = match result {
+                Ok(feed) => {
+                    fetch::update_history(&mut history, &config, &feed);
+                    FeedState::Success(feed)
+                }
+                Err(err) => {
+                    eprintln!("{name}: {err}");
+                    failed += 1;
+                    FeedState::Error(err)
+                }
+            };
+            kiosk.insert_feed(name, state);
+        }
+        history.save()?;
+
+        let digest = Digest {
+            format: self.format,
+            since,
+            until,
+            summary_length: config
+                .digest
+                .as_ref()
+                .and_then(|x| x.summary_length)
+                .unwrap_or(DEFAULT_SUMMARY_LENGTH),
+            sources: sources
+                .iter()
+                .filter_map(|name| {
+                    let articles: Vec<&Article> = kiosk
+                        .get_feed(name)?
+                        .articles()
+                        .filter(|x| article_date(x).is_some_and(|date| date >= since))
+                        .filter(|x| !self.unread_only || !history.is_article_read(name, x))
+                        .collect();
+                    (!articles.is_empty()).then(|| (name.clone(), articles))
+                })
+                .collect(),
+        };
+        print!("{}", digest.render(&template));
+
+        Ok(exit_code(failed, sources.len()))
+    }
+
+    /// Read the template: the one provided with `--template`, otherwise the one configured for the format or the default one
+    fn template(&self, config: &Config) -> Result<String, String> {
+        let digest_config = config.digest.clone().unwrap_or_default();
+        let path = self.template.clone().or(match self.format {
+            DigestFormat::Html => digest_config.html_template,
+            DigestFormat::Markdown => digest_config.markdown_template,
+        });
+        match (path, self.format) {
+            (Some(path), _) => std::fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read template {}: {err}", path.display())),
+            (None, DigestFormat::Html) => Ok(HTML_TEMPLATE.to_string()),
+            (None, DigestFormat::Markdown) => Ok(MARKDOWN_TEMPLATE.to_string()),
+        }
+    }
+}
+
+/// Remove the rest of the marker line from the start of a template section
+fn strip_marker_line(section: &str) -> &str {
+    section
+        .strip_prefix("\r\n")
+        .or_else(|| section.strip_prefix('\n'))
+        .unwrap_or(section)
+}
+
+/// Get the date of `article`: the publication date, or the update date if it has none
+fn article_date(article: &Article) -> Option<DateTime<Local>> {
+    article.date.or(article.updated)
+}
+
+/// Get `url` if it is a http or https url, so links can't run scripts; otherwise an empty string
+fn safe_url(url: &str) -> &str {
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme.to_ascii_lowercase());
+    match scheme.as_deref() {
+        Some("http" | "https") => url,
+        _ => "",
+    }
+}
+
+/// Escape the characters which have a special meaning in Markdown text and links
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if matches!(
+            ch,
+            '\\' | '[' | ']' | '(' | ')' | '*' | '_' | '`' | '<' | '>'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Escape the characters which have a special meaning in HTML
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod test {
+
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_since() {
+        assert_eq!(
+            Since::from_str("30m").unwrap(),
+            Since(Duration::from_secs(1800))
+        );
+        assert_eq!(
+            Since::from_str("12h").unwrap(),
+            Since(Duration::from_secs(43200))
+        );
+        assert_eq!(
+            Since::from_str("7d").unwrap(),
+            Since(Duration::from_secs(604800))
+        );
+        assert_eq!(
+            Since::from_str("2w").unwrap(),
+            Since(Duration::from_secs(1209600))
+        );
+        assert!(Since::from_str("").is_err());
+        assert!(Since::from_str("d").is_err());
+        assert!(Since::from_str("7y").is_err());
+        assert!(Since::from_str("18446744073709551615w").is_err());
+    }
+
+    #[test]
+    fn should_render_markdown_digest() {
+        let first = article(
+            "Embedding shared objects in Rust",
+            &["veeso"],
+            "A long summary\nabout linking",
+        );
+        let second = article("Vendoring C/C++ dependencies", &[], "short");
+        let digest = digest(
+            DigestFormat::Markdown,
+            vec![("blog", vec![&first, &second])],
+            20,
+        );
+        let output = digest.render(MARKDOWN_TEMPLATE);
+        assert!(
+            output.starts_with(
+                "# tuifeed digest\n\n2025-03-14 00:00 - 2025-03-21 00:00\n\n## blog\n"
+            )
+        );
+        assert!(output.contains(
+            "### [Embedding shared objects in Rust](https://blog.veeso.dev/embedding-shared-objects-in-rust/)\n\n*2025-03-20 16:00* by veeso\n\nA long summary abou…\n"
+        ));
+        assert!(output.contains("### [Vendoring C/C++ dependencies]("));
+        assert!(!output.contains(SOURCE_MARKER));
+    }
+
+    #[test]
+    fn should_render_html_digest() {
+        let first = article("Tom & Jerry <3", &[], "summary");
+        let digest = digest(DigestFormat::Html, vec![("blog", vec![&first])], 280);
+        let output = digest.render(HTML_TEMPLATE);
+        assert!(output.contains("<h2>blog</h2>"));
+        assert!(output.contains(">Tom &amp; Jerry &lt;3</a></h3>"));
+        assert!(output.ends_with("    </article>\n  </body>\n</html>\n"));
+    }
+
+    #[test]
+    fn should_escape_markdown() {
+        let mut first = article("Tips_and_tricks [part 1] (*draft*)", &[], "summary");
+        first.url = String::from("https://example.com/a_(b)");
+        let digest = digest(DigestFormat::Markdown, vec![("blog", vec![&first])], 280);
+        let output = digest.render(MARKDOWN_TEMPLATE);
+        assert!(output.contains(
+            r"### [Tips\_and\_tricks \[part 1\] \(\*draft\*\)](https://example.com/a\_\(b\))"
+        ));
+    }
+
+    #[test]
+    fn should_render_only_web_links() {
+        let mut first = article("Click me", &[], "summary");
+        first.url = String::from("javascript:alert(1)");
+        let digest = digest(DigestFormat::Html, vec![("blog", vec![&first])], 280);
+        let output = digest.render(HTML_TEMPLATE);
+        assert!(output.contains(r#"<h3><a href="">Click me</a></h3>"#));
+        assert_eq!(safe_url("HTTPS://example.com"), "HTTPS://example.com");
+        assert_eq!(safe_url("data:text/html,hi"), "");
+        assert_eq!(safe_url("example.com"), "");
+    }
+
+    #[test]
+    fn should_render_custom_template() {
+        let first = article("{url}", &[], "summary");
+        let digest = digest(DigestFormat::Markdown, vec![("blog", vec![&first])], 280);
+        assert_eq!(
+            digest.render("{count} articles\n<!-- source -->\n{source}: \n<!-- article -->\n- {title} {unknown}\n"),
+            "1 articles\nblog: \n- {url} {unknown}\n"
+        );
+    }
+
+    fn digest<'a>(
+        format: DigestFormat,
+        sources: Vec<(&str, Vec<&'a Article>)>,
+        summary_length: usize,
+    ) -> Digest<'a> {
+        Digest {
+            format,
+            since: Local.with_ymd_and_hms(2025, 3, 14, 0, 0, 0).unwrap(),
+            until: Local.with_ymd_and_hms(2025, 3, 21, 0, 0, 0).unwrap(),
+            summary_length,
+            sources: sources
+                .into_iter()
+                .map(|(name, articles)| (name.to_string(), articles))
+                .collect(),
+        }
+    }
+
+    fn article(title: &str, authors: &[&str], summary: &str) -> Article {
+        Article {
+            title: Some(title.to_string()),
+            authors: authors.iter().map(|x| x.to_string()).collect(),
+            summary: summary.to_string(),
+            url: String::from("https://blog.veeso.dev/embedding-shared-objects-in-rust/"),
+            date: Some(Local.with_ymd_and_hms(2025, 3, 20, 16, 0, 0).unwrap()),
+            ..Article::mock(title)
+        }
+    }
+}
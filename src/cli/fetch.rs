@@ -0,0 +1,370 @@
+//! # Fetch
+//!
+//! Headless fetch of the configured sources
+
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use argh::FromArgs;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::feed::{Article, Client, Feed, FeedError, FeedResult};
+use crate::ui::{History, Retention};
+
+/// Exit code returned when some of the sources couldn't be fetched
+const EXIT_SOURCES_FAILED: u8 = 2;
+/// Exit code returned when none of the sources could be fetched
+const EXIT_ALL_SOURCES_FAILED: u8 = 3;
+/// Maximum amount of sources fetched at the same time
+const FETCH_WORKERS: usize = 8;
+
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "fetch",
+    description = "fetch the configured sources and print their articles without starting the ui",
+    note = "Exit codes: 0 if all sources have been fetched, 1 on error, 2 if some sources failed, 3 if all sources failed."
+)]
+pub struct FetchArgs {
+    #[argh(
+        option,
+        default = "FetchFormat::Plain",
+        description = "output format: plain, json or ndjson; default plain"
+    )]
+    format: FetchFormat,
+    #[argh(switch, description = "mark the printed articles as read")]
+    mark_read: bool,
+    #[argh(
+        option,
+        description = "fetch only the source with this name; can be repeated"
+    )]
+    source: Vec<String>,
+    #[argh(switch, description = "print only the unread articles")]
+    unread_only: bool,
+}
+
+/// Output format of the fetched articles
+enum FetchFormat {
+    Json,
+    Ndjson,
+    Plain,
+}
+
+impl FromStr for FetchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            "plain" => Ok(Self::Plain),
+            _ => Err(format!(
+                "unknown format \"{s}\"; expected plain, json or ndjson"
+            )),
+        }
+    }
+}
+
+/// An article fetched from a source, with its read state
+#[derive(Debug, Serialize)]
+pub struct FetchedArticle {
+    pub source: String,
+    pub id: String,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub url: String,
+    /// Publication date, as RFC 3339
+    pub date: Option<String>,
+    pub read: bool,
+    /// The article has changed since it has been read
+    pub updated: bool,
+}
+
+impl FetchedArticle {
+    fn new(source: &str, article: &Article, history: &History) -> Self {
+        Self {
+            source: source.to_string(),
+            id: article.id.clone(),
+            title: article.title.clone(),
+            authors: article.authors.clone(),
+            url: article.url.clone(),
+            date: article.date.map(|x| x.to_rfc3339()),
+            read: history.is_article_read(source, article),
+            updated: history.is_article_updated(source, article),
+        }
+    }
+
+    /// Format the article as a tab separated line: read state, date, source, title and url
+    fn plain(&self) -> String {
+        let state = if self.read { "read" } else { "unread" };
+        [
+            state,
+            self.date.as_deref().unwrap_or("-"),
+            &self.source,
+            self.title.as_deref().unwrap_or_default(),
+            &self.url,
+        ]
+        .map(|x| x.replace(['\t', '\n', '\r'], " "))
+        .join("\t")
+    }
+}
+
+impl FetchArgs {
+    pub(super) fn run(
+        self,
+        profile: Option<&str>,
+        config_path: Option<&Path>,
+    ) -> Result<ExitCode, Box<dyn std::error::Error>> {
+        let config = crate::init_config(config_path)?;
+        let sources = select_sources(&config, &self.source)?;
+        let mut history = History::load(&History::default_path(profile)?)?;
+        if let Some(warning) = history.take_warning() {
+            eprintln!("{warning}");
+        }
+        history.set_source_identities(config.source_identities());
+        history.set_retention(Retention::from(&config.history.clone().unwrap_or_default()));
+
+        let mut articles = Vec::new();
+        let mut failed = 0;
+        for (name, result) in fetch_sources(&config, &sources) {
+            let feed = match result {
+                Ok(feed) => feed,
+                Err(err) => {
+                    eprintln!("{name}: {err}");
+                    failed += 1;
+                    continue;
+                }
+            };
+            update_history(&mut history, &config, &feed);
+            for article in feed.articles() {
+                let fetched = FetchedArticle::new(&name, article, &history);
+                if self.unread_only && fetched.read {
+                    continue;
+                }
+                if self.mark_read {
+                    history.read(&name, article);
+                }
+                articles.push(fetched);
+            }
+        }
+        history.save()?;
+
+        match self.format {
+            FetchFormat::Json => println!("{}", serde_json::to_string_pretty(&articles)?),
+            FetchFormat::Ndjson => {
+                for article in &articles {
+                    println!("{}", serde_json::to_string(article)?);
+                }
+            }
+            FetchFormat::Plain => {
+                for article in &articles {
+                    println!("{}", article.plain());
+                }
+            }
+        }
+
+        Ok(exit_code(failed, sources.len()))
+    }
+}
+
+/// Get the names of the sources to fetch, sorted: `names` if not empty, otherwise all the configured sources.
+/// Fails if a name is not a configured source
+pub(super) fn select_sources(config: &Config, names: &[String]) -> Result<Vec<String>, String> {
+    let mut sources: Vec<String> = if names.is_empty() {
+        config.sources.keys().cloned().collect()
+    } else {
+        if let Some(name) = names.iter().find(|x| !config.sources.contains_key(*x)) {
+            return Err(format!("Unknown source \"{name}\""));
+        }
+        names.to_vec()
+    };
+    sources.sort();
+    sources.dedup();
+
+    Ok(sources)
+}
+
+/// Fetch `sources` concurrently with at most [`FETCH_WORKERS`] threads, returning the result for each source in the
+/// same order
+pub(super) fn fetch_sources(
+    config: &Config,
+    sources: &[String],
+) -> Vec<(String, FeedResult<Feed>)> {
+    let sources: Vec<_> = sources
+        .iter()
+        .filter_map(|name| config.sources.get(name).map(|x| (name, &x.source)))
+        .collect();
+    let results: Vec<Mutex<Option<FeedResult<Feed>>>> =
+        sources.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..FETCH_WORKERS.min(sources.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    // each worker takes the next source to fetch, until there are none left
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((name, source)) = sources.get(index) else {
+                            break;
+                        };
+                        let result = Client.fetch(name, source);
+                        *results[index]
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner) = Some(result);
+                    }
+                })
+            })
+            .collect();
+        // a panicked worker leaves its source without result
+        for worker in workers {
+            let _ = worker.join();
+        }
+    });
+    sources
+        .into_iter()
+        .zip(results)
+        .map(|((name, _), result)| {
+            let result = result
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .unwrap_or_else(|| Err(FeedError::Io(String::from("fetch worker panicked"))));
+            (name.clone(), result)
+        })
+        .collect()
+}
+
+/// Store the articles of the fetched `feed` in the history, as the ui does
+pub(super) fn update_history(history: &mut History, config: &Config, feed: &Feed) {
+    let articles: Vec<_> = feed.articles().collect();
+    history.filter_articles(&feed.name, &articles);
+    let updates_unread = config.updates_unread(&feed.name);
+    for article in articles {
+        history.insert(&feed.name, article, updates_unread);
+    }
+}
+
+/// Get the exit code, given the amount of `failed` sources among `total`
+pub(super) fn exit_code(failed: usize, total: usize) -> ExitCode {
+    match failed {
+        0 => ExitCode::SUCCESS,
+        failed if failed == total => ExitCode::from(EXIT_ALL_SOURCES_FAILED),
+        _ => ExitCode::from(EXIT_SOURCES_FAILED),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::Write as _;
+
+    use pretty_assertions::assert_eq;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::config::SourceConfig;
+    use crate::feed::FeedSource;
+
+    #[test]
+    fn should_select_sources() {
+        let config = config(&[
+            ("lemonde", "/tmp/lemonde.xml"),
+            ("figaro", "/tmp/figaro.xml"),
+        ]);
+        assert_eq!(
+            select_sources(&config, &[]).unwrap(),
+            vec!["figaro", "lemonde"]
+        );
+        assert_eq!(
+            select_sources(&config, &[String::from("lemonde")]).unwrap(),
+            vec!["lemonde"]
+        );
+        assert!(select_sources(&config, &[String::from("nytimes")]).is_err());
+    }
+
+    #[test]
+    fn should_fetch_sources_concurrently() {
+        let mut feed = NamedTempFile::new().unwrap();
+        feed.write_all(FEED.as_bytes()).unwrap();
+        let missing = feed.path().with_extension("missing");
+        let config = config(&[
+            ("blog", feed.path().to_str().unwrap()),
+            ("missing", missing.to_str().unwrap()),
+        ]);
+        let sources = select_sources(&config, &[]).unwrap();
+        let results = fetch_sources(&config, &sources);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.as_str(), "blog");
+        let feed = results[0].1.as_ref().unwrap();
+        assert_eq!(feed.articles().count(), 2);
+        assert!(results[1].1.is_err());
+
+        let mut history = History::default();
+        update_history(&mut history, &config, feed);
+        let article = feed.articles().next().unwrap();
+        history.read("blog", article);
+        let fetched = FetchedArticle::new("blog", article, &history);
+        assert!(fetched.read);
+        assert_eq!(
+            fetched.plain(),
+            format!(
+                "read\t{}\tblog\tEmbedding shared objects in Rust\thttps://blog.veeso.dev/embedding/",
+                article.date.unwrap().to_rfc3339()
+            )
+        );
+    }
+
+    #[test]
+    fn should_fetch_more_sources_than_workers() {
+        let mut feed = NamedTempFile::new().unwrap();
+        feed.write_all(FEED.as_bytes()).unwrap();
+        let names: Vec<String> = (0..FETCH_WORKERS * 2 + 1)
+            .map(|i| format!("blog{i:02}"))
+            .collect();
+        let path = feed.path().to_str().unwrap();
+        let config = config(
+            &names
+                .iter()
+                .map(|name| (name.as_str(), path))
+                .collect::<Vec<_>>(),
+        );
+        let results = fetch_sources(&config, &names);
+        assert_eq!(
+            results.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            names.iter().collect::<Vec<_>>()
+        );
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+    }
+
+    #[test]
+    fn should_get_exit_code() {
+        assert_eq!(exit_code(0, 3), ExitCode::SUCCESS);
+        assert_eq!(exit_code(1, 3), ExitCode::from(EXIT_SOURCES_FAILED));
+        assert_eq!(exit_code(3, 3), ExitCode::from(EXIT_ALL_SOURCES_FAILED));
+    }
+
+    fn config(sources: &[(&str, &str)]) -> Config {
+        Config {
+            sources: sources
+                .iter()
+                .map(|(name, path)| {
+                    (
+                        name.to_string(),
+                        SourceConfig::from(FeedSource::File(path.into())),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0"><channel><title>Blog</title><link>https://blog.veeso.dev</link>
+<item><title>Embedding shared objects in Rust</title><link>https://blog.veeso.dev/embedding/</link><guid>embedding</guid><pubDate>Fri, 21 Mar 2025 16:00:00 GMT</pubDate></item>
+<item><title>Vendoring C/C++ dependencies in Rust</title><link>https://blog.veeso.dev/vendoring/</link><guid>vendoring</guid><pubDate>Thu, 20 Mar 2025 17:00:00 GMT</pubDate></item>
+</channel></rss>"#;
+}
@@ -2,28 +2,521 @@
 //!
 //! Configuration types for tuifeed
 
+mod dates;
 pub mod serializer;
 
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-use serde::Deserialize;
+use lazy_regex::Regex;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 
+pub use self::dates::{DateFormat, Timezone};
+use self::serializer::SourceChange;
 use crate::feed::FeedSource;
 
+/// Name of the feed where the articles of all sources are merged; it can't be used by a source
+pub const MERGED_FEED_NAME: &str = "All sources";
+
 /// tuifeed configuration
 #[derive(Deserialize, Clone, Debug, Default)]
 pub struct Config {
     /// Article title configuration
     #[serde(rename = "article-title")]
     pub article_title: Option<ArticleTitleConfig>,
-    /// Association between source name and url
-    pub sources: HashMap<String, FeedSource>,
+    /// Clipboard configuration
+    pub clipboard: Option<ClipboardConfig>,
+    /// Dates configuration
+    pub dates: Option<DatesConfig>,
+    /// Digest export configuration
+    pub digest: Option<DigestConfig>,
+    /// Enclosures download configuration
+    pub downloads: Option<DownloadsConfig>,
+    /// Duplicate articles configuration
+    pub duplicates: Option<DuplicatesConfig>,
+    /// Read history configuration
+    pub history: Option<HistoryConfig>,
+    /// Media configuration
+    pub media: Option<MediaConfig>,
+    /// Commands used to open urls; the first matching opener is used
+    #[serde(default)]
+    pub openers: Vec<OpenerConfig>,
+    /// External reader configuration
+    pub reader: Option<ReaderConfig>,
+    /// Association between source name and source configuration
+    #[serde(deserialize_with = "deserialize_sources")]
+    pub sources: HashMap<String, SourceConfig>,
+}
+
+impl Config {
+    /// Get the timezone used to display dates
+    pub fn timezone(&self) -> Timezone {
+        self.dates
+            .as_ref()
+            .and_then(|x| x.timezone)
+            .unwrap_or_default()
+    }
+
+    /// Get the first opener matching `url` or `mime`
+    pub fn opener(&self, url: &str, mime: Option<&str>) -> Option<&OpenerConfig> {
+        self.openers.iter().find(|x| x.matches(url, mime))
+    }
+
+    /// Get the opener for the media at `url`: the configured player, which takes precedence over openers, otherwise
+    /// the first opener which matches the media
+    pub fn media_opener(&self, url: &str, mime: Option<&str>) -> Option<OpenerConfig> {
+        match self.media.as_ref().and_then(|x| x.player.clone()) {
+            Some(player) => Some(OpenerConfig {
+                pattern: None,
+                scheme: None,
+                mime: None,
+                command: player,
+                terminal: false,
+            }),
+            None => self.opener(url, mime).cloned(),
+        }
+    }
+
+    /// Returns whether articles must be marked as read when selected
+    pub fn mark_read_on_select(&self) -> bool {
+        self.history
+            .as_ref()
+            .and_then(|x| x.mark_read_on_select)
+            .unwrap_or(true)
+    }
+
+    /// Get the amount of days without reads after which a source is reported as inactive in statistics.
+    /// Defaults to 30
+    pub fn inactive_days(&self) -> u64 {
+        self.history
+            .as_ref()
+            .and_then(|x| x.inactive_days)
+            .unwrap_or(30)
+    }
+
+    /// Get the command used to read articles in an external program.
+    /// Defaults to `$PAGER`, or `less` if not set
+    pub fn reader_command(&self) -> String {
+        self.reader
+            .as_ref()
+            .and_then(|x| x.command.clone())
+            .or_else(|| std::env::var("PAGER").ok().filter(|x| !x.trim().is_empty()))
+            .unwrap_or_else(|| String::from("less"))
+    }
+
+    /// Returns whether the feed where the articles of all sources are merged must be shown
+    pub fn merged_view(&self) -> bool {
+        self.duplicates
+            .as_ref()
+            .and_then(|x| x.merged_view)
+            .unwrap_or_default()
+    }
+
+    /// Get the stable identity of each source, by source name
+    pub fn source_identities(&self) -> HashMap<String, String> {
+        self.sources
+            .iter()
+            .map(|(name, source)| (name.clone(), source.identity()))
+            .collect()
+    }
+
+    /// Returns whether articles of `source` updated after being read must be marked as unread
+    pub fn updates_unread(&self, source: &str) -> bool {
+        self.sources
+            .get(source)
+            .and_then(|x| x.updates_unread)
+            .unwrap_or_default()
+    }
+
+    /// Apply a change to the configured sources
+    pub fn apply_source_change(&mut self, change: &SourceChange) {
+        match change {
+            SourceChange::Add(name, source) => {
+                self.sources
+                    .insert(name.clone(), SourceConfig::from(source.clone()));
+            }
+            SourceChange::Edit(name, source) => {
+                self.sources
+                    .entry(name.clone())
+                    .or_insert_with(|| SourceConfig::from(source.clone()))
+                    .source = source.clone();
+            }
+            SourceChange::Rename(name, new_name) => {
+                if let Some(source) = self.sources.remove(name) {
+                    self.sources.insert(new_name.clone(), source);
+                }
+            }
+            SourceChange::Remove(name) => {
+                self.sources.remove(name);
+            }
+        }
+    }
+
+    /// Compare the sources with the sources of `other`, which is the newer configuration
+    pub fn diff_sources(&self, other: &Config) -> SourcesDiff {
+        let mut diff = SourcesDiff::default();
+        for (name, source) in other.sources.iter() {
+            match self.sources.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(current) if current != source => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .sources
+            .keys()
+            .filter(|name| !other.sources.contains_key(*name))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.changed.sort();
+        diff.removed.sort();
+        diff
+    }
+
+    /// Returns whether any of the configured date formats is relative, and so must be updated over time
+    pub fn has_relative_dates(&self) -> bool {
+        let title_format = self
+            .article_title
+            .as_ref()
+            .and_then(|x| x.timestamp_format.as_ref());
+        let article_format = self.dates.as_ref().and_then(|x| x.format.as_ref());
+        title_format.is_some_and(DateFormat::is_relative)
+            || article_format.is_some_and(DateFormat::is_relative)
+    }
+}
+
+/// Deserialize the sources, refusing the name of the merged feed, which would be hidden by it
+fn deserialize_sources<'a, D>(deserializer: D) -> Result<HashMap<String, SourceConfig>, D::Error>
+where
+    D: Deserializer<'a>,
+{
+    let sources = HashMap::<String, SourceConfig>::deserialize(deserializer)?;
+    if sources.contains_key(MERGED_FEED_NAME) {
+        return Err(serde::de::Error::custom(format!(
+            r#"the source name "{MERGED_FEED_NAME}" is reserved"#
+        )));
+    }
+    Ok(sources)
+}
+
+/// Differences between the sources of two configurations
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourcesDiff {
+    /// Names of the new sources
+    pub added: Vec<String>,
+    /// Names of the sources whose url has changed
+    pub changed: Vec<String>,
+    /// Names of the sources which don't exist anymore
+    pub removed: Vec<String>,
+}
+
+impl SourcesDiff {
+    /// Returns whether sources are unchanged
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
 }
 
 /// article title configuration
-#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[derive(Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct ArticleTitleConfig {
     pub show_timestamp: bool,
     pub show_author: bool,
+    /// Format of the timestamp in the article list; either a strftime format or `relative`.
+    /// Defaults to RFC3339
+    pub timestamp_format: Option<DateFormat>,
+}
+
+/// clipboard configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ClipboardConfig {
+    /// Whether to copy through the OSC 52 escape sequence. Defaults to `true`
+    pub osc52: Option<bool>,
+    /// Command the copied text is piped to (e.g. `wl-copy`), run after the OSC 52 sequence has been written
+    pub command: Option<String>,
+}
+
+/// dates configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct DatesConfig {
+    /// Format of the date in the article pane; either a strftime format or `relative`
+    pub format: Option<DateFormat>,
+    /// Timezone used to display dates: `local`, `UTC` or an IANA timezone (e.g. `Europe/Rome`)
+    pub timezone: Option<Timezone>,
+}
+
+/// media configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct MediaConfig {
+    /// Command used to play enclosures (e.g. `mpv --no-video`); the enclosure url is appended as last argument
+    pub player: Option<String>,
+}
+
+/// duplicate articles configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct DuplicatesConfig {
+    /// How articles of different sources are compared to find duplicates; if empty, duplicates are not detected
+    #[serde(default)]
+    pub match_by: Vec<DuplicateMatch>,
+    /// Minimum similarity between the titles of duplicate articles, from 0 to 1, when matching by title.
+    /// Defaults to 0.8
+    pub title_similarity: Option<f64>,
+    /// Whether to show a feed with the articles of all sources, where duplicates are merged. Defaults to `false`
+    pub merged_view: Option<bool>,
+}
+
+/// Describes how duplicate articles are matched
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateMatch {
+    /// Same article id
+    Guid,
+    /// Similar title
+    Title,
+    /// Same url, once normalized
+    Url,
+}
+
+/// read history configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct HistoryConfig {
+    /// Sources where nothing has been read for this amount of days are reported as inactive in statistics.
+    /// Defaults to 30
+    pub inactive_days: Option<u64>,
+    /// Whether articles are marked as read when selected. Defaults to `true`.
+    /// If disabled, articles are marked as read when opened, when scrolled to the end or explicitly
+    pub mark_read_on_select: Option<bool>,
+    /// Amount of days the read state of articles dropped from their feed is kept for. Defaults to 90
+    pub retention_days: Option<u64>,
+    /// Maximum amount of articles dropped from their feed kept for each source. Defaults to 1000
+    pub retention_max_articles: Option<usize>,
+    /// Directory shared between machines (e.g. a synced folder) where the history is synchronized through
+    pub sync_dir: Option<PathBuf>,
+}
+
+/// external reader configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReaderConfig {
+    /// Command the article text is piped to (e.g. `w3m`, `lynx -stdin`); it runs through the shell
+    pub command: Option<String>,
+}
+
+/// url opener configuration
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct OpenerConfig {
+    /// Regex the url must match
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    pub pattern: Option<Regex>,
+    /// Scheme of the url (e.g. `gemini`)
+    pub scheme: Option<String>,
+    /// MIME type of the resource; a `type/*` wildcard is supported
+    pub mime: Option<String>,
+    /// Command used to open the url; `%u` is replaced with the url,
+    /// otherwise the url is appended as last argument
+    pub command: String,
+    /// Whether the command runs in the terminal, so the ui must be suspended until it terminates
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl OpenerConfig {
+    /// Returns whether the opener matches the url and MIME type.
+    /// All the provided criteria must match; an opener without criteria matches any url
+    pub fn matches(&self, url: &str, mime: Option<&str>) -> bool {
+        let pattern_matches = self.pattern.as_ref().is_none_or(|x| x.is_match(url));
+        let scheme_matches = self.scheme.as_deref().is_none_or(|scheme| {
+            url.split_once(':')
+                .is_some_and(|(x, _)| x.eq_ignore_ascii_case(scheme))
+        });
+        let mime_matches = self.mime.as_deref().is_none_or(|expected| {
+            mime.is_some_and(|mime| match expected.strip_suffix("/*") {
+                Some(kind) => mime.split('/').next() == Some(kind),
+                None => mime.eq_ignore_ascii_case(expected),
+            })
+        });
+        pattern_matches && scheme_matches && mime_matches
+    }
+}
+
+fn deserialize_regex<'a, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'a>,
+{
+    let Some(pattern) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    Regex::new(&pattern)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// source configuration; either the source url or a table with the url and the source options
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceConfig {
+    pub source: FeedSource,
+    /// Explicit identity of the source, used instead of the normalized url to key its read history
+    pub id: Option<String>,
+    /// Whether articles updated after being read are marked as unread. Defaults to `false`
+    pub updates_unread: Option<bool>,
+}
+
+impl From<FeedSource> for SourceConfig {
+    fn from(source: FeedSource) -> Self {
+        Self {
+            source,
+            id: None,
+            updates_unread: None,
+        }
+    }
+}
+
+impl SourceConfig {
+    /// Get the stable identity of the source: the explicit `id` if set, otherwise the normalized url
+    pub fn identity(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.source.identity())
+    }
+}
+
+/// source configured as a table
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SourceTable {
+    url: FeedSource,
+    id: Option<String>,
+    updates_unread: Option<bool>,
+}
+
+impl<'a> Deserialize<'a> for SourceConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        struct SourceConfigVisitor;
+
+        impl<'a> Visitor<'a> for SourceConfigVisitor {
+            type Value = SourceConfig;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a source url or a table with the source url and options")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                FeedSource::from_str(v)
+                    .map(SourceConfig::from)
+                    .map_err(E::custom)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'a>,
+            {
+                let table = SourceTable::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(SourceConfig {
+                    source: table.url,
+                    id: table.id,
+                    updates_unread: table.updates_unread,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(SourceConfigVisitor)
+    }
+}
+
+/// digest export configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct DigestConfig {
+    /// Template file used for HTML digests, instead of the default one
+    pub html_template: Option<PathBuf>,
+    /// Template file used for Markdown digests, instead of the default one
+    pub markdown_template: Option<PathBuf>,
+    /// Maximum length of the article summaries, in characters
+    pub summary_length: Option<usize>,
+}
+
+/// enclosures download configuration
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct DownloadsConfig {
+    /// Directory where enclosures are downloaded
+    pub directory: Option<PathBuf>,
+    /// File name template; supports `{feed}`, `{date}` and `{title}`.
+    /// The extension of the enclosure is appended to the file name
+    pub template: Option<String>,
+    /// Maximum amount of concurrent downloads
+    pub workers: Option<usize>,
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn config_with_sources(sources: &[(&str, &str)]) -> Config {
+        Config {
+            sources: sources
+                .iter()
+                .map(|(name, url)| {
+                    (
+                        name.to_string(),
+                        SourceConfig::from(FeedSource::from_str(url).unwrap()),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_diff_sources() {
+        let current = config_with_sources(&[
+            (
+                "lefigaro",
+                "https://www.lefigaro.fr/rss/figaro_actualites.xml",
+            ),
+            (
+                "nytimes",
+                "https://rss.nytimes.com/services/xml/rss/nyt/World.xml",
+            ),
+            ("ilpost", "https://www.ilpost.it/feed/"),
+        ]);
+        let new = config_with_sources(&[
+            (
+                "lefigaro",
+                "https://www.lefigaro.fr/rss/figaro_actualites.xml",
+            ),
+            (
+                "nytimes",
+                "https://rss.nytimes.com/services/xml/rss/nyt/Europe.xml",
+            ),
+            ("lemonde", "https://www.lemonde.fr/rss/une.xml"),
+        ]);
+        let diff = current.diff_sources(&new);
+        assert_eq!(diff.added, vec![String::from("lemonde")]);
+        assert_eq!(diff.changed, vec![String::from("nytimes")]);
+        assert_eq!(diff.removed, vec![String::from("ilpost")]);
+        assert!(!diff.is_empty());
+        assert!(current.diff_sources(&current).is_empty());
+    }
 }
@@ -0,0 +1,176 @@
+//! # Dates
+//!
+//! Date format and timezone configuration
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use crate::helpers::fmt as fmt_helpers;
+
+const RELATIVE: &str = "relative";
+
+/// Describes how a date is displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateFormat {
+    /// Relative to now (e.g. `3h ago`, `yesterday`)
+    Relative,
+    /// strftime format string
+    Format(String),
+}
+
+impl DateFormat {
+    /// Format `datetime` in the provided timezone
+    pub fn format(&self, datetime: DateTime<Local>, timezone: &Timezone) -> String {
+        let now = Local::now();
+        match (self, timezone) {
+            (Self::Relative, Timezone::Local) => fmt_helpers::format_relative(datetime, now),
+            (Self::Relative, Timezone::Utc) => {
+                fmt_helpers::format_relative(datetime.with_timezone(&Utc), now.with_timezone(&Utc))
+            }
+            (Self::Relative, Timezone::Named(tz)) => {
+                fmt_helpers::format_relative(datetime.with_timezone(tz), now.with_timezone(tz))
+            }
+            (Self::Format(fmt), Timezone::Local) => fmt_helpers::format_datetime(datetime, fmt),
+            (Self::Format(fmt), Timezone::Utc) => {
+                fmt_helpers::format_datetime(datetime.with_timezone(&Utc), fmt)
+            }
+            (Self::Format(fmt), Timezone::Named(tz)) => {
+                fmt_helpers::format_datetime(datetime.with_timezone(tz), fmt)
+            }
+        }
+    }
+
+    /// Returns whether the date is relative to now, and so must be updated over time
+    pub fn is_relative(&self) -> bool {
+        matches!(self, Self::Relative)
+    }
+}
+
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Relative => write!(f, "{RELATIVE}"),
+            Self::Format(fmt) => write!(f, "{fmt}"),
+        }
+    }
+}
+
+impl FromStr for DateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case(RELATIVE) {
+            return Ok(Self::Relative);
+        }
+        // validate format string
+        if chrono::format::StrftimeItems::new(s).parse().is_err() {
+            return Err(format!("Invalid date format: {s}"));
+        }
+
+        Ok(Self::Format(s.to_string()))
+    }
+}
+
+impl<'a> Deserialize<'a> for DateFormat {
+    fn deserialize<D>(deserializer: D) -> Result<DateFormat, D::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        let s = String::deserialize(deserializer)?;
+        DateFormat::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Timezone used to display dates
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Timezone {
+    #[default]
+    Local,
+    Utc,
+    /// IANA timezone (e.g. `Europe/Rome`)
+    Named(Tz),
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Utc => write!(f, "UTC"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            Ok(Self::Local)
+        } else if s.eq_ignore_ascii_case("utc") {
+            Ok(Self::Utc)
+        } else {
+            Tz::from_str(s)
+                .map(Self::Named)
+                .map_err(|_| format!("Invalid timezone: {s}"))
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for Timezone {
+    fn deserialize<D>(deserializer: D) -> Result<Timezone, D::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Timezone::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_date_format() {
+        assert_eq!(DateFormat::from_str("relative"), Ok(DateFormat::Relative));
+        assert_eq!(
+            DateFormat::from_str("%d/%m %H:%M"),
+            Ok(DateFormat::Format(String::from("%d/%m %H:%M")))
+        );
+        assert!(DateFormat::from_str("%Q").is_err());
+    }
+
+    #[test]
+    fn should_parse_timezone() {
+        assert_eq!(Timezone::from_str("local"), Ok(Timezone::Local));
+        assert_eq!(Timezone::from_str("UTC"), Ok(Timezone::Utc));
+        assert_eq!(
+            Timezone::from_str("Europe/Rome"),
+            Ok(Timezone::Named(chrono_tz::Europe::Rome))
+        );
+        assert!(Timezone::from_str("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn should_format_date_in_timezone() {
+        let datetime = Utc
+            .with_ymd_and_hms(2025, 3, 21, 16, 0, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let format = DateFormat::Format(String::from("%Y-%m-%d %H:%M"));
+        assert_eq!(format.format(datetime, &Timezone::Utc), "2025-03-21 16:00");
+        assert_eq!(
+            format.format(datetime, &Timezone::Named(chrono_tz::Asia::Tokyo)),
+            "2025-03-22 01:00"
+        );
+    }
+}
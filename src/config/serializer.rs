@@ -2,10 +2,18 @@
 //!
 //! Configuration seralizer
 
-use std::io::Read;
+use std::io::{Read, Write};
 
 use serde::de::DeserializeOwned;
 use thiserror::Error;
+use toml_edit::{DocumentMut, Item, Key, Table, Value};
+
+use crate::feed::FeedSource;
+
+/// Name of the table containing sources
+const SOURCES_TABLE: &str = "sources";
+/// Key of the url of a source configured as a table
+const SOURCE_URL_KEY: &str = "url";
 
 /// Contains the error for serializer/deserializer
 #[derive(Debug)]
@@ -19,10 +27,43 @@ pub struct SerializerError {
 pub enum SerializerErrorKind {
     #[error("IO error")]
     Io,
+    #[error("Source error")]
+    Source,
     #[error("Syntax error")]
     Syntax,
 }
 
+/// Describes a change to the sources in the configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceChange {
+    /// Add a new source with name and url
+    Add(String, FeedSource),
+    /// Change the url of a source
+    Edit(String, FeedSource),
+    /// Rename a source
+    Rename(String, String),
+    /// Remove a source
+    Remove(String),
+}
+
+impl SourceChange {
+    /// Name of the source the change applies to, before the change
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Add(name, _) | Self::Edit(name, _) | Self::Remove(name) => name,
+            Self::Rename(name, _) => name,
+        }
+    }
+
+    /// Source the change sets, if any
+    pub fn source(&self) -> Option<&FeedSource> {
+        match self {
+            Self::Add(_, source) | Self::Edit(_, source) => Some(source),
+            Self::Rename(..) | Self::Remove(_) => None,
+        }
+    }
+}
+
 impl SerializerError {
     /// Instantiates a new `SerializerError` with description message
     pub fn new(kind: SerializerErrorKind, msg: String) -> SerializerError {
@@ -60,16 +101,127 @@ where
     }
 }
 
+/// Read TOML data from readable, apply `change` to its sources and write the result to writable.
+/// Comments and formatting of the document are preserved
+pub fn serialize<R, W>(
+    mut readable: R,
+    mut writable: W,
+    change: &SourceChange,
+) -> Result<(), SerializerError>
+where
+    R: Read,
+    W: Write,
+{
+    // Read file content
+    let mut data: String = String::new();
+    if let Err(err) = readable.read_to_string(&mut data) {
+        return Err(SerializerError::new(
+            SerializerErrorKind::Io,
+            err.to_string(),
+        ));
+    }
+    let mut document: DocumentMut = data.parse().map_err(|err: toml_edit::TomlError| {
+        SerializerError::new(SerializerErrorKind::Syntax, err.to_string())
+    })?;
+    let sources = document
+        .entry(SOURCES_TABLE)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| {
+            SerializerError::new(
+                SerializerErrorKind::Syntax,
+                format!("`{SOURCES_TABLE}` is not a table"),
+            )
+        })?;
+    apply_source_change(sources, change)?;
+    // Write document
+    writable
+        .write_all(document.to_string().as_bytes())
+        .map_err(|err| SerializerError::new(SerializerErrorKind::Io, err.to_string()))
+}
+
+/// Apply `change` to the sources table
+fn apply_source_change(sources: &mut Table, change: &SourceChange) -> Result<(), SerializerError> {
+    let source_error = |msg: String| SerializerError::new(SerializerErrorKind::Source, msg);
+    match change {
+        SourceChange::Add(name, source) => {
+            if sources.contains_key(name) {
+                return Err(source_error(format!("Source {name} already exists")));
+            }
+            sources.insert(name, toml_edit::value(source.to_string()));
+        }
+        SourceChange::Edit(name, source) => {
+            let item = sources
+                .get_mut(name)
+                .ok_or_else(|| source_error(format!("Source {name} doesn't exist")))?;
+            // keep the options of a source configured as a table
+            match item.as_table_like_mut() {
+                Some(table) => match table.get_mut(SOURCE_URL_KEY) {
+                    Some(url) => set_value(url, source.to_string()),
+                    None => {
+                        table.insert(SOURCE_URL_KEY, toml_edit::value(source.to_string()));
+                    }
+                },
+                None => set_value(item, source.to_string()),
+            }
+        }
+        SourceChange::Rename(from, to) => {
+            if sources.contains_key(to) {
+                return Err(source_error(format!("Source {to} already exists")));
+            }
+            if !sources.contains_key(from) {
+                return Err(source_error(format!("Source {from} doesn't exist")));
+            }
+            // re-insert all the entries in order, in order to keep the position of the renamed source
+            let entries: Vec<(Key, Item)> = sources
+                .iter()
+                .map(|(key, item)| (sources.key(key).cloned().unwrap(), item.clone()))
+                .collect();
+            sources.clear();
+            for (key, item) in entries {
+                if key.get() == from {
+                    let mut renamed = Key::new(to.as_str());
+                    *renamed.leaf_decor_mut() = key.leaf_decor().clone();
+                    *renamed.dotted_decor_mut() = key.dotted_decor().clone();
+                    sources.insert_formatted(&renamed, item);
+                } else {
+                    sources.insert_formatted(&key, item);
+                }
+            }
+        }
+        SourceChange::Remove(name) => {
+            if sources.remove(name).is_none() {
+                return Err(source_error(format!("Source {name} doesn't exist")));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the value of `item`, keeping the comments around it
+fn set_value(item: &mut Item, value: String) {
+    match item.as_value_mut() {
+        Some(current) => {
+            let decor = current.decor().clone();
+            *current = Value::from(value);
+            *current.decor_mut() = decor;
+        }
+        None => *item = toml_edit::value(value),
+    }
+}
+
 #[cfg(test)]
 mod test {
 
     use std::fs::File;
     use std::io::Write;
+    use std::str::FromStr;
 
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::config::Config;
+    use crate::config::{Config, DateFormat, Timezone};
 
     #[test]
     fn should_create_serialization_errors() {
@@ -91,15 +243,68 @@ mod test {
         let config: Config = deserialize(Box::new(reader)).ok().unwrap();
         assert_eq!(config.sources.len(), 2);
         assert_eq!(
-            config.sources.get("nytimes").unwrap().to_string(),
+            config.sources.get("nytimes").unwrap().source.to_string(),
             "https://rss.nytimes.com/services/xml/rss/nyt/World.xml"
         );
         assert_eq!(
-            config.sources.get("lefigaro").unwrap().to_string(),
+            config.sources.get("lefigaro").unwrap().source.to_string(),
             "https://www.lefigaro.fr/rss/figaro_actualites.xml"
         );
         assert_eq!(config.article_title.as_ref().unwrap().show_author, true);
         assert_eq!(config.article_title.as_ref().unwrap().show_timestamp, false);
+        assert_eq!(
+            config.article_title.as_ref().unwrap().timestamp_format,
+            Some(DateFormat::Relative)
+        );
+        assert_eq!(
+            config.dates.as_ref().unwrap().format,
+            Some(DateFormat::Format(String::from("%d/%m/%Y %H:%M")))
+        );
+        assert_eq!(config.timezone(), Timezone::Utc);
+        assert!(config.has_relative_dates());
+        assert_eq!(config.openers.len(), 3);
+        assert_eq!(
+            config
+                .opener("https://www.youtube.com/watch?v=1", None)
+                .map(|x| x.command.as_str()),
+            Some("mpv %u")
+        );
+        assert_eq!(
+            config
+                .opener("https://example.com/paper", Some("application/pdf"))
+                .map(|x| x.command.as_str()),
+            Some("zathura")
+        );
+        let opener = config.opener("https://example.com/", None).unwrap();
+        assert_eq!(opener.command.as_str(), "w3m");
+        assert!(opener.terminal);
+        assert_eq!(config.reader_command().as_str(), "w3m -T text/plain");
+        assert!(!config.mark_read_on_select());
+        assert!(config.updates_unread("lefigaro"));
+        assert!(!config.updates_unread("nytimes"));
+        let identities = config.source_identities();
+        assert_eq!(identities.get("lefigaro").unwrap().as_str(), "figaro");
+        assert_eq!(
+            identities.get("nytimes").unwrap().as_str(),
+            "rss.nytimes.com/services/xml/rss/nyt/World.xml"
+        );
+        assert_eq!(
+            config.media.as_ref().unwrap().player.as_deref(),
+            Some("mpv --no-video")
+        );
+        // the player takes precedence over the opener matching any url
+        let opener = config
+            .media_opener("https://example.com/episode.mp3", Some("audio/mpeg"))
+            .unwrap();
+        assert_eq!(opener.command.as_str(), "mpv --no-video");
+        assert!(!opener.terminal);
+        let digest = config.digest.as_ref().unwrap();
+        assert_eq!(
+            digest.markdown_template.as_deref(),
+            Some(std::path::Path::new("/home/user/.config/tuifeed/digest.md"))
+        );
+        assert!(digest.html_template.is_none());
+        assert_eq!(digest.summary_length, Some(120));
     }
 
     #[test]
@@ -109,14 +314,19 @@ mod test {
         let config: Config = deserialize(Box::new(reader)).ok().unwrap();
         assert_eq!(config.sources.len(), 2);
         assert_eq!(
-            config.sources.get("nytimes").unwrap().to_string(),
+            config.sources.get("nytimes").unwrap().source.to_string(),
             "https://rss.nytimes.com/services/xml/rss/nyt/World.xml"
         );
         assert_eq!(
-            config.sources.get("lefigaro").unwrap().to_string(),
+            config.sources.get("lefigaro").unwrap().source.to_string(),
             "https://www.lefigaro.fr/rss/figaro_actualites.xml"
         );
         assert!(config.article_title.is_none());
+        assert!(config.media.is_none());
+        assert_eq!(config.timezone(), Timezone::Local);
+        assert!(!config.has_relative_dates());
+        assert!(config.openers.is_empty());
+        assert!(config.mark_read_on_select());
     }
 
     #[test]
@@ -126,16 +336,206 @@ mod test {
         assert!(deserialize::<File, Config>(reader).is_err());
     }
 
+    #[test]
+    fn should_serialize_source_changes() {
+        let document = r##"# my feeds
+[article-title]
+show-author = true
+show-timestamp = false
+
+[sources]
+# world news
+nytimes = "https://rss.nytimes.com/services/xml/rss/nyt/World.xml" # the best
+lefigaro = "https://www.lefigaro.fr/rss/figaro_actualites.xml"
+"##;
+        let document = change(
+            document,
+            &SourceChange::Add(
+                String::from("lemonde"),
+                FeedSource::from_str("https://www.lemonde.fr/rss/une.xml").unwrap(),
+            ),
+        )
+        .unwrap();
+        let document = change(
+            &document,
+            &SourceChange::Rename(String::from("nytimes"), String::from("nyt")),
+        )
+        .unwrap();
+        let document = change(
+            &document,
+            &SourceChange::Edit(
+                String::from("nyt"),
+                FeedSource::from_str("https://rss.nytimes.com/services/xml/rss/nyt/Europe.xml")
+                    .unwrap(),
+            ),
+        )
+        .unwrap();
+        let document = change(&document, &SourceChange::Remove(String::from("lefigaro"))).unwrap();
+        assert_eq!(
+            document,
+            r##"# my feeds
+[article-title]
+show-author = true
+show-timestamp = false
+
+[sources]
+# world news
+nyt = "https://rss.nytimes.com/services/xml/rss/nyt/Europe.xml" # the best
+lemonde = "https://www.lemonde.fr/rss/une.xml"
+"##
+        );
+        // the result can be deserialized
+        let config: Config = deserialize(document.as_bytes()).unwrap();
+        assert_eq!(config.sources.len(), 2);
+    }
+
+    #[test]
+    fn should_keep_source_options_when_editing_source() {
+        let document = r##"[sources]
+nytimes = { url = "https://rss.nytimes.com/services/xml/rss/nyt/World.xml", updates-unread = true }
+
+[sources.lefigaro]
+url = "https://www.lefigaro.fr/rss/figaro_actualites.xml" # news
+updates-unread = false
+"##;
+        let document = change(
+            document,
+            &SourceChange::Edit(
+                String::from("nytimes"),
+                FeedSource::from_str("https://rss.nytimes.com/services/xml/rss/nyt/Europe.xml")
+                    .unwrap(),
+            ),
+        )
+        .unwrap();
+        let document = change(
+            &document,
+            &SourceChange::Edit(
+                String::from("lefigaro"),
+                FeedSource::from_str("https://www.lefigaro.fr/rss/figaro_economie.xml").unwrap(),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            document,
+            r##"[sources]
+nytimes = { url = "https://rss.nytimes.com/services/xml/rss/nyt/Europe.xml", updates-unread = true }
+
+[sources.lefigaro]
+url = "https://www.lefigaro.fr/rss/figaro_economie.xml" # news
+updates-unread = false
+"##
+        );
+        let config: Config = deserialize(document.as_bytes()).unwrap();
+        assert!(config.updates_unread("nytimes"));
+        assert!(!config.updates_unread("lefigaro"));
+    }
+
+    #[test]
+    fn should_fail_deserializing_invalid_source_table() {
+        assert!(
+            deserialize::<_, Config>("[sources]\nnytimes = { updates-unread = true }".as_bytes())
+                .is_err()
+        );
+        assert!(deserialize::<_, Config>("[sources]\nnytimes = 1".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn should_fail_deserializing_reserved_source_name() {
+        let config = r#"[sources]
+"All sources" = "https://www.nytimes.com/svc/collections/v1/publish/https://www.nytimes.com/section/world/rss.xml"
+"#;
+        assert!(deserialize::<_, Config>(config.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn should_add_sources_table() {
+        let document = change(
+            "",
+            &SourceChange::Add(
+                String::from("lemonde"),
+                FeedSource::from_str("https://www.lemonde.fr/rss/une.xml").unwrap(),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            document,
+            "[sources]\nlemonde = \"https://www.lemonde.fr/rss/une.xml\"\n"
+        );
+    }
+
+    #[test]
+    fn should_fail_serializing_invalid_changes() {
+        let document = r##"[sources]
+nytimes = "https://rss.nytimes.com/services/xml/rss/nyt/World.xml"
+lefigaro = "https://www.lefigaro.fr/rss/figaro_actualites.xml"
+"##;
+        assert!(
+            change(
+                document,
+                &SourceChange::Add(
+                    String::from("nytimes"),
+                    FeedSource::from_str("https://example.com/feed.xml").unwrap()
+                )
+            )
+            .is_err()
+        );
+        assert!(
+            change(
+                document,
+                &SourceChange::Rename(String::from("nytimes"), String::from("lefigaro"))
+            )
+            .is_err()
+        );
+        assert!(change(document, &SourceChange::Remove(String::from("lemonde"))).is_err());
+        assert!(change("[sources", &SourceChange::Remove(String::from("lemonde"))).is_err());
+    }
+
+    fn change(document: &str, change: &SourceChange) -> Result<String, SerializerError> {
+        let mut output = Vec::new();
+        serialize(document.as_bytes(), &mut output, change)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
     fn create_good_toml_config() -> tempfile::NamedTempFile {
         let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
         let file_content: &str = r##"
         [article-title]
         show-author = true
         show-timestamp = false
+        timestamp-format = "relative"
+
+        [dates]
+        format = "%d/%m/%Y %H:%M"
+        timezone = "UTC"
+
+        [digest]
+        markdown-template = "/home/user/.config/tuifeed/digest.md"
+        summary-length = 120
+
+        [media]
+        player = "mpv --no-video"
+
+        [[openers]]
+        pattern = "^https://(www\\.)?youtube\\.com/"
+        command = "mpv %u"
+
+        [[openers]]
+        mime = "application/pdf"
+        command = "zathura"
+
+        [[openers]]
+        command = "w3m"
+        terminal = true
+
+        [reader]
+        command = "w3m -T text/plain"
+
+        [history]
+        mark-read-on-select = false
 
         [sources]
         nytimes = "https://rss.nytimes.com/services/xml/rss/nyt/World.xml"
-        lefigaro = "https://www.lefigaro.fr/rss/figaro_actualites.xml"
+        lefigaro = { url = "https://www.lefigaro.fr/rss/figaro_actualites.xml", id = "figaro", updates-unread = true }
         "##;
         tmpfile.write_all(file_content.as_bytes()).unwrap();
         tmpfile
@@ -9,6 +9,7 @@ mod result;
 mod source;
 
 use std::slice::Iter;
+use std::time::Duration;
 
 // -- deps
 use chrono::{DateTime, Local};
@@ -17,7 +18,7 @@ use feed_rs::model::{Entry as RssEntry, Feed as RssFeed};
 
 pub use self::client::Client;
 pub use self::result::{FeedError, FeedResult};
-pub use self::source::FeedSource;
+pub use self::source::{FeedSource, normalize_url};
 use crate::helpers::strings as str_helpers;
 
 /// Contains, for a feed source, the list of articles fetched from remote
@@ -34,8 +35,25 @@ pub struct Article {
     pub title: Option<String>,
     pub authors: Vec<String>,
     pub summary: String,
+    /// Links referenced in the summary by their footnote marker (e.g. `[1]` is at index 0)
+    pub links: Vec<String>,
     pub url: String,
     pub date: Option<DateTime<Local>>,
+    /// Time the article has been last updated, if reported by the feed
+    pub updated: Option<DateTime<Local>>,
+    /// Media attached to the article (e.g. podcast episodes)
+    pub enclosures: Vec<Enclosure>,
+}
+
+/// A media object attached to an article
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enclosure {
+    pub url: String,
+    /// MIME type of the media
+    pub mime: Option<String>,
+    /// Size in bytes
+    pub size: Option<u64>,
+    pub duration: Option<Duration>,
 }
 
 impl Feed {
@@ -54,46 +72,127 @@ impl Feed {
     }
 }
 
+impl Article {
+    /// Get a fingerprint of the article content, which changes when the article is edited
+    pub fn fingerprint(&self) -> u64 {
+        fnv1a([self.title.as_deref().unwrap_or_default(), &self.summary])
+    }
+}
+
+#[cfg(test)]
+impl Article {
+    /// Make an article with `id` and no content; the other fields can be set with the struct update syntax
+    pub fn mock(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: None,
+            authors: vec![],
+            summary: String::default(),
+            links: vec![],
+            url: String::default(),
+            date: None,
+            updated: None,
+            enclosures: vec![],
+        }
+    }
+}
+
 impl From<RssEntry> for Article {
     fn from(entry: RssEntry) -> Self {
-        let content_or_summary = content_or_summary(&entry);
+        let url = entry
+            .links
+            .first()
+            .map(|x| x.href.clone())
+            .unwrap_or(entry.id.clone());
+        let (summary, links) = content_or_summary(&entry, &url);
+        let enclosures = enclosures(&entry);
         Self {
             id: entry.id.clone(),
             title: entry
                 .title
                 .map(|x| str_helpers::strip_html(x.content.as_str())),
             authors: entry.authors.into_iter().map(|x| x.name).collect(),
-            summary: content_or_summary,
-            url: entry
-                .links
-                .first()
-                .map(|x| x.href.clone())
-                .unwrap_or(entry.id),
+            summary,
+            links,
+            url,
             date: entry
                 .published
                 .or(entry.updated)
                 .map(DateTime::<Local>::from),
+            updated: entry.updated.map(DateTime::<Local>::from),
+            enclosures,
         }
     }
 }
 
+/// Collect the enclosures of the entry.
+/// RSS `<enclosure>` and MediaRSS elements are provided by feed-rs as media, while Atom enclosures are links with `rel="enclosure"`
+fn enclosures(entry: &RssEntry) -> Vec<Enclosure> {
+    let mut enclosures: Vec<Enclosure> = Vec::new();
+    let media = entry.media.iter().flat_map(|object| {
+        object.content.iter().filter_map(|content| {
+            content.url.as_ref().map(|url| Enclosure {
+                url: url.to_string(),
+                mime: content.content_type.as_ref().map(|x| x.to_string()),
+                size: content.size,
+                duration: content.duration.or(object.duration),
+            })
+        })
+    });
+    let links = entry
+        .links
+        .iter()
+        .filter(|link| link.rel.as_deref() == Some("enclosure"))
+        .map(|link| Enclosure {
+            url: link.href.clone(),
+            mime: link.media_type.clone(),
+            size: link.length,
+            duration: None,
+        });
+    for enclosure in media.chain(links) {
+        if !enclosures.iter().any(|x| x.url == enclosure.url) {
+            enclosures.push(enclosure);
+        }
+    }
+
+    enclosures
+}
+
+/// Hash `parts` with the 64 bit FNV-1a algorithm, which is stable across builds and platforms
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    parts
+        .into_iter()
+        // separate parts, so that moving text from a part to another changes the hash
+        .flat_map(|part| part.bytes().chain(std::iter::once(0)))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
 /// This function returns content if any, otherwise the summary of the article.
-/// The reason is that content is USUALLY the entire article, BUT sometimes is not filled, so summary is preferred in these cases
-fn content_or_summary(entry: &RssEntry) -> String {
-    let content = entry
+/// The reason is that content is USUALLY the entire article, BUT sometimes is not filled, so summary is preferred in these cases.
+/// Along with the text, the links found in the article body are returned, resolved against `url`.
+fn content_or_summary(entry: &RssEntry, url: &str) -> (String, Vec<String>) {
+    let (content, links) = entry
         .content
         .as_ref()
-        .and_then(|x| x.body.as_ref().map(|x| str_helpers::strip_html(x)))
+        .and_then(|x| {
+            x.body
+                .as_ref()
+                .map(|x| str_helpers::strip_html_with_links(x, url))
+        })
         .unwrap_or_default();
     if content.trim_matches('\n').trim().is_empty() {
         // get summary instead
         entry
             .summary
             .as_ref()
-            .map(|x| str_helpers::strip_html(x.content.as_str()))
+            .map(|x| str_helpers::strip_html_with_links(x.content.as_str(), url))
             .unwrap_or_default()
     } else {
-        content
+        (content, links)
     }
 }
 
@@ -121,10 +220,100 @@ mod test {
         assert!(article.authors.is_empty());
         assert_eq!(article.date, None);
         assert_eq!(article.summary, String::new());
+        assert!(article.links.is_empty());
+        assert!(article.enclosures.is_empty());
         assert_eq!(article.title, None);
         assert_eq!(article.url, String::new());
     }
 
+    #[test]
+    fn should_get_article_fingerprint() {
+        assert_eq!(fnv1a([]), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(["a"]), fnv1a(["a"]));
+        assert_ne!(fnv1a(["ab", ""]), fnv1a(["a", "b"]));
+
+        let mut article = Article::from(RssEntry::default());
+        let fingerprint = article.fingerprint();
+        article.summary.push_str("edited");
+        assert_ne!(article.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn should_convert_entry_with_links_into_article() {
+        let entry = RssEntry {
+            links: vec![feed_rs::model::Link {
+                href: String::from("https://example.com/blog/post"),
+                rel: None,
+                media_type: None,
+                href_lang: None,
+                title: None,
+                length: None,
+            }],
+            summary: Some(feed_rs::model::Text {
+                content_type: "text/html".parse().unwrap(),
+                src: None,
+                content: String::from(r#"See <a href="/about">about</a>"#),
+            }),
+            ..Default::default()
+        };
+        let article = Article::from(entry);
+        assert_eq!(article.summary.as_str(), "See about[1]");
+        assert_eq!(
+            article.links,
+            vec![String::from("https://example.com/about")]
+        );
+    }
+
+    #[test]
+    fn should_get_enclosures_from_rss() {
+        let feed = feed_rs::parser::parse(PODCAST_FEED.as_bytes()).unwrap();
+        let article = Article::from(feed.entries.into_iter().next().unwrap());
+        assert_eq!(
+            article.enclosures,
+            vec![Enclosure {
+                url: String::from("https://example.com/episode-1.mp3"),
+                mime: Some(String::from("audio/mpeg")),
+                size: Some(24986239),
+                duration: Some(Duration::from_secs(3723)),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_get_enclosures_from_atom_links() {
+        let entry = RssEntry {
+            links: vec![
+                feed_rs::model::Link {
+                    href: String::from("https://example.com/post"),
+                    rel: Some(String::from("alternate")),
+                    media_type: None,
+                    href_lang: None,
+                    title: None,
+                    length: None,
+                },
+                feed_rs::model::Link {
+                    href: String::from("https://example.com/video.mp4"),
+                    rel: Some(String::from("enclosure")),
+                    media_type: Some(String::from("video/mp4")),
+                    href_lang: None,
+                    title: None,
+                    length: Some(1337),
+                },
+            ],
+            ..Default::default()
+        };
+        let article = Article::from(entry);
+        assert_eq!(
+            article.enclosures,
+            vec![Enclosure {
+                url: String::from("https://example.com/video.mp4"),
+                mime: Some(String::from("video/mp4")),
+                size: Some(1337),
+                duration: None,
+            }]
+        );
+    }
+
     #[test]
     fn should_convert_rssfeed_into_feed() {
         let feed = RssFeed {
@@ -150,4 +339,19 @@ mod test {
         let feed = Feed::new("pippo", feed);
         assert_eq!(feed.articles.len(), 2);
     }
+
+    const PODCAST_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+<channel>
+<title>Podcast</title>
+<link>https://example.com</link>
+<item>
+<title>Episode 1</title>
+<link>https://example.com/episode-1</link>
+<guid>https://example.com/episode-1</guid>
+<enclosure url="https://example.com/episode-1.mp3" length="24986239" type="audio/mpeg"/>
+<itunes:duration>01:02:03</itunes:duration>
+</item>
+</channel>
+</rss>"#;
 }
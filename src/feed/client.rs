@@ -50,14 +50,13 @@ mod test {
 
     #[test]
     fn should_get_source() {
-        let client = Client::default();
+        let client = Client;
         assert!(
             client
                 .fetch(
                     "The New York Times",
                     &(String::from("https://rss.nytimes.com/services/xml/rss/nyt/World.xml")
-                        .try_into()
-                        .unwrap())
+                        .into())
                 )
                 .is_ok()
         );
@@ -65,7 +64,7 @@ mod test {
 
     #[test]
     fn should_fail_getting_source() {
-        let client = Client::default();
+        let client = Client;
         assert!(
             client
                 .fetch(
@@ -73,8 +72,7 @@ mod test {
                     &(String::from(
                         "https://rss.nytimes.com/services/xml/rss/nyt/pippopippopippo.xml"
                     )
-                    .try_into()
-                    .unwrap())
+                    .into())
                 )
                 .is_err()
         );
@@ -88,8 +86,7 @@ mod test {
                     "The New York Times",
                     &("https://rss.nytimes.com/services/xml/rss/nyt/World.xml"
                         .to_string()
-                        .try_into()
-                        .unwrap())
+                        .into())
                 )
                 .is_ok()
         );
@@ -99,8 +96,7 @@ mod test {
                     "The Guardian",
                     &("https://www.lefigaro.fr/rss/figaro_actualites.xml"
                         .to_string()
-                        .try_into()
-                        .unwrap())
+                        .into())
                 )
                 .is_ok()
         );
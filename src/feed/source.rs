@@ -3,8 +3,11 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 const FILE_PREFIX: &str = "file://";
+/// Query parameters used for tracking, which are ignored when comparing urls
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "mc_cid", "mc_eid", "ref"];
 
 /// A source for a feed
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -43,6 +46,17 @@ impl FromStr for FeedSource {
     }
 }
 
+impl FeedSource {
+    /// Get a stable identity of the source, which doesn't change with cosmetic changes to its url
+    /// (e.g. `http` to `https`, trailing slash, tracking parameters)
+    pub fn identity(&self) -> String {
+        match self {
+            FeedSource::Http(url) => normalize_url(url).unwrap_or_else(|| url.clone()),
+            FeedSource::File(_) => self.to_string(),
+        }
+    }
+}
+
 impl From<String> for FeedSource {
     fn from(s: String) -> Self {
         FeedSource::from_str(s.as_str()).unwrap()
@@ -68,6 +82,30 @@ impl<'a> Deserialize<'a> for FeedSource {
     }
 }
 
+/// Normalize `url`, so that different urls of the same resource are equal:
+/// scheme, `www.` prefix, fragment, trailing slash and tracking parameters are ignored
+pub fn normalize_url(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    let mut normalized = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    if let Some(port) = url.port() {
+        normalized.push_str(&format!(":{port}"));
+    }
+    normalized.push_str(url.path().trim_end_matches('/'));
+    let mut query: Vec<String> = url
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    query.sort();
+    if !query.is_empty() {
+        normalized.push('?');
+        normalized.push_str(&query.join("&"));
+    }
+
+    Some(normalized)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -103,6 +141,39 @@ mod test {
         assert_eq!(FeedSource::from_str(source), Err(expected.to_string()));
     }
 
+    #[test]
+    fn should_normalize_urls() {
+        assert_eq!(
+            normalize_url("https://www.Example.com/news/story/?utm_source=rss&id=3#comments")
+                .as_deref(),
+            Some("example.com/news/story?id=3")
+        );
+        assert_eq!(
+            normalize_url("http://example.com/news/story"),
+            normalize_url("https://www.example.com/news/story/?fbclid=abc")
+        );
+        assert_eq!(normalize_url("not an url"), None);
+    }
+
+    #[test]
+    fn should_get_source_identity() {
+        assert_eq!(
+            FeedSource::from_str("https://www.example.com/feed.xml/?utm_source=x")
+                .unwrap()
+                .identity(),
+            FeedSource::from_str("http://example.com/feed.xml")
+                .unwrap()
+                .identity()
+        );
+        assert_eq!(
+            FeedSource::from_str("file:///path/to/feed.xml")
+                .unwrap()
+                .identity()
+                .as_str(),
+            "file:///path/to/feed.xml"
+        );
+    }
+
     #[test]
     fn test_should_fail_parsing_relative_path() {
         let source = "file://path/to/feed.xml";
@@ -3,6 +3,7 @@
 //! Tuifeed application helpers
 
 // -- helpers
+pub mod clipboard;
 pub mod file;
 pub mod fmt;
 pub mod open;
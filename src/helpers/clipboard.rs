@@ -0,0 +1,123 @@
+//! # Clipboard
+//!
+//! Clipboard helpers
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Copy `text` to the clipboard of the terminal emulator through the OSC 52 escape sequence.
+/// This works over SSH too; when running inside tmux, the sequence is passed through to the outer terminal
+pub fn copy_osc52(text: &str) -> Result<(), String> {
+    let inside_tmux = std::env::var_os("TMUX").is_some();
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(osc52_sequence(text, inside_tmux).as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(|e| format!("Could not copy to clipboard: {e}"))
+}
+
+/// Copy `text` to the clipboard by writing it to the standard input of `command` (e.g. `wl-copy`)
+pub fn copy_with(command: &str, text: &str) -> Result<(), String> {
+    let mut args = command.split_whitespace();
+    let Some(program) = args.next() else {
+        return Err(String::from("Empty command"));
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Could not run `{program}`: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Could not copy to clipboard: {e}"))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|e| format!("Could not run `{program}`: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("`{program}` exited with {status}"))
+    }
+}
+
+/// Make the OSC 52 sequence which sets the clipboard to `text`.
+/// Inside tmux the sequence is wrapped into a DCS passthrough sequence
+fn osc52_sequence(text: &str, inside_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if inside_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Encode `data` as base64, with padding
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64("perché".as_bytes()), "cGVyY2jDqQ==");
+    }
+
+    #[test]
+    fn should_make_osc52_sequence() {
+        assert_eq!(osc52_sequence("foo", false), "\x1b]52;c;Zm9v\x07");
+        assert_eq!(
+            osc52_sequence("foo", true),
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_copy_with_command() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let script = temp.path().with_extension("sh");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\ncat > {}\n", temp.path().display()),
+        )
+        .unwrap();
+        assert!(copy_with(&format!("sh {}", script.display()), "hello").is_ok());
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), "hello");
+        let _ = std::fs::remove_file(script);
+        assert!(copy_with("false", "hello").is_err());
+    }
+}
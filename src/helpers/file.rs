@@ -16,6 +16,34 @@ pub fn write_file(p: &Path, content: &str) -> Result<(), IoError> {
     File::create(p)?.write_all(content.as_bytes())
 }
 
+/// Write `content` to file located at `p` atomically.
+/// Content is written to a temporary file next to `p`, which then replaces `p`; if writing fails, `p` is left untouched.
+/// If `p` is a symlink, the file it points to is replaced, and the permissions of the replaced file are kept
+pub fn write_file_atomic(p: &Path, content: impl AsRef<[u8]>) -> Result<(), IoError> {
+    let (p, permissions) = match std::fs::canonicalize(p) {
+        Ok(target) => {
+            let permissions = std::fs::metadata(&target)?.permissions();
+            (target, Some(permissions))
+        }
+        Err(_) => (p.to_path_buf(), None),
+    };
+    let mut tmp_name = p.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = p.with_file_name(tmp_name);
+    let result = File::create(&tmp).and_then(|mut file| {
+        file.write_all(content.as_ref())?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()
+    });
+    if let Err(err) = result {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(err);
+    }
+    std::fs::rename(&tmp, p)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -29,6 +57,40 @@ mod test {
         assert!(write_file(temp.path(), "Hello world!\n").is_ok());
     }
 
+    #[test]
+    fn should_write_file_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        assert!(write_file(&path, "old").is_ok());
+        assert!(write_file_atomic(&path, "new").is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert!(!dir.path().join("history.json.tmp").exists());
+        // can't write into a missing directory
+        assert!(write_file_atomic(&dir.path().join("missing/history.json"), "new").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_write_symlinked_file_atomically() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dotfiles/config.toml");
+        std::fs::create_dir(target.parent().unwrap()).unwrap();
+        assert!(write_file(&target, "old").is_ok());
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.path().join("config.toml");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(write_file_atomic(&link, "new").is_ok());
+        assert!(std::fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
     #[test]
     fn should_open_file_for_read() {
         let temp = NamedTempFile::new().ok().unwrap();
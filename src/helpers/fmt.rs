@@ -2,19 +2,91 @@
 //!
 //! Tuifeed formatting helpers
 
-use chrono::{DateTime, Local};
+use std::fmt::Display;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone};
 
 /// Format datetime according to provided format
-pub fn format_datetime(datetime: DateTime<Local>, fmt: &str) -> String {
+pub fn format_datetime<Tz: TimeZone>(datetime: DateTime<Tz>, fmt: &str) -> String
+where
+    Tz::Offset: Display,
+{
     datetime.format(fmt).to_string()
 }
 
+/// Format datetime relatively to `now` (e.g. `3h ago`, `yesterday`).
+/// Dates older than a week are formatted as plain dates
+pub fn format_relative<Tz: TimeZone>(datetime: DateTime<Tz>, now: DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    let elapsed = now.clone().signed_duration_since(datetime.clone());
+    let days = now
+        .date_naive()
+        .signed_duration_since(datetime.date_naive())
+        .num_days();
+    if elapsed.num_minutes() < 1 {
+        String::from("just now")
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if days == 0 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if days == 1 {
+        String::from("yesterday")
+    } else if days < 7 {
+        format!("{days} days ago")
+    } else {
+        format_datetime(datetime, "%d %b %Y")
+    }
+}
+
+/// Format a size in bytes to a human readable representation (e.g. `23.8 MiB`)
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Format duration as `H:MM:SS`, or `MM:SS` if shorter than an hour
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Format a long duration with its two most significant units (e.g. `3d 4h`, `2h 15m`, `40m`)
+pub fn format_delay(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
 #[cfg(test)]
 mod test {
 
     use std::ops::Add;
     use std::time::{Duration, SystemTime};
 
+    use chrono::{Local, TimeDelta, Utc};
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -26,4 +98,51 @@ mod test {
             .into();
         assert_eq!(format_datetime(datetime, "%Y-%m-%d"), "1970-01-01");
     }
+
+    #[test]
+    fn should_format_relative_datetime() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 21, 16, 0, 0).unwrap();
+        assert_eq!(format_relative(now, now), "just now");
+        assert_eq!(
+            format_relative(now - TimeDelta::minutes(12), now),
+            "12m ago"
+        );
+        assert_eq!(format_relative(now - TimeDelta::hours(3), now), "3h ago");
+        assert_eq!(
+            format_relative(now - TimeDelta::hours(20), now),
+            "yesterday"
+        );
+        assert_eq!(format_relative(now - TimeDelta::days(4), now), "4 days ago");
+        assert_eq!(
+            format_relative(now - TimeDelta::days(30), now),
+            "19 Feb 2025"
+        );
+    }
+
+    #[test]
+    fn should_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KiB");
+        assert_eq!(format_bytes(24986239), "23.8 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn should_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+        assert_eq!(format_duration(Duration::from_secs(125)), "02:05");
+    }
+
+    #[test]
+    fn should_format_delay() {
+        assert_eq!(format_delay(Duration::from_secs(40 * 60 + 12)), "40m");
+        assert_eq!(
+            format_delay(Duration::from_secs(2 * 3600 + 15 * 60)),
+            "2h 15m"
+        );
+        assert_eq!(
+            format_delay(Duration::from_secs(3 * 86400 + 4 * 3600)),
+            "3d 4h"
+        );
+    }
 }
@@ -2,9 +2,13 @@
 //!
 //! Open helpers
 
+use std::io::Write;
 use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
 
 use open;
+use url::Url;
 
 /// Open text file
 pub fn open_text_file(p: &Path) -> Result<(), String> {
@@ -18,3 +22,183 @@ pub fn open_text_file(p: &Path) -> Result<(), String> {
 pub fn open_link(link: &str) -> Result<(), String> {
     open::that(link).map_err(|e| e.to_string())
 }
+
+/// Open `target` with the provided command.
+/// The command is split on whitespaces; `%u` arguments are replaced with `target`,
+/// otherwise `target` is appended as last argument.
+/// The process is detached from the terminal and its output discarded
+pub fn open_with(command: &str, target: &str) -> Result<(), String> {
+    let (program, args) = command_args(command, target)?;
+    let mut child = Command::new(&program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Could not run `{program}`: {e}"))?;
+    // reap the process once it terminates
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+/// Run the provided command on `target` in the foreground, attached to the terminal, and wait for it to terminate.
+/// Arguments are built as in [`open_with`]
+pub fn run_in_terminal(command: &str, target: &str) -> Result<(), String> {
+    let (program, args) = command_args(command, target)?;
+    let status = Command::new(&program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("Could not run `{program}`: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("`{program}` exited with {status}"))
+    }
+}
+
+/// Run `command` through the shell, attached to the terminal, and write `content` to its standard input.
+/// Waits for the program to terminate
+pub fn pipe_to(command: &str, content: &str) -> Result<(), String> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run `{command}`: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the program may terminate before reading the whole content (e.g. the pager is closed); that's fine
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let status = child
+        .wait()
+        .map_err(|e| format!("Could not run `{command}`: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("`{command}` exited with {status}"))
+    }
+}
+
+/// Make a command which runs `command` through the system shell
+fn shell_command(command: &str) -> Command {
+    #[cfg(unix)]
+    {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+    #[cfg(windows)]
+    {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+}
+
+/// Guess the MIME type of the resource at `url` from its extension
+pub fn guess_mime(url: &str) -> Option<&'static str> {
+    let mime = match url_extension(url)?.to_lowercase().as_str() {
+        "pdf" => "application/pdf",
+        "epub" => "application/epub+zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Get the file extension from the path of `url`, ignoring the query and the fragment
+pub fn url_extension(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let file_name = url.path_segments()?.next_back()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    Some(extension.to_string()).filter(|x| !x.is_empty())
+}
+
+/// Split command into program and arguments, replacing `%u` with `target`.
+/// If the command has no `%u` placeholder, `target` is appended as last argument
+fn command_args(command: &str, target: &str) -> Result<(String, Vec<String>), String> {
+    let mut args = command.split_whitespace();
+    let Some(program) = args.next() else {
+        return Err(String::from("Empty command"));
+    };
+    let mut args: Vec<String> = args.map(|x| x.replace("%u", target)).collect();
+    if !command.contains("%u") {
+        args.push(target.to_string());
+    }
+
+    Ok((program.to_string(), args))
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_make_command_args() {
+        assert_eq!(
+            command_args("mpv --no-video", "https://example.com/a.mp3").unwrap(),
+            (
+                String::from("mpv"),
+                vec![
+                    String::from("--no-video"),
+                    String::from("https://example.com/a.mp3")
+                ]
+            )
+        );
+        assert_eq!(
+            command_args("w3m %u -o display_image=0", "https://example.com").unwrap(),
+            (
+                String::from("w3m"),
+                vec![
+                    String::from("https://example.com"),
+                    String::from("-o"),
+                    String::from("display_image=0")
+                ]
+            )
+        );
+        assert!(command_args("  ", "https://example.com").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_pipe_to_command() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        assert!(pipe_to(&format!("cat > {}", temp.path().display()), "hello").is_ok());
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), "hello");
+        assert!(pipe_to("exit 3", "hello").is_err());
+    }
+
+    #[test]
+    fn should_guess_mime() {
+        assert_eq!(
+            guess_mime("https://example.com/paper.PDF?dl=1"),
+            Some("application/pdf")
+        );
+        assert_eq!(guess_mime("https://example.com/ep.mp3"), Some("audio/mpeg"));
+        assert_eq!(guess_mime("https://example.com/article"), None);
+    }
+
+    #[test]
+    fn should_get_url_extension() {
+        assert_eq!(
+            url_extension("https://example.com/ep.mp3?token=a.b#t=1").as_deref(),
+            Some("mp3")
+        );
+        assert_eq!(url_extension("https://example.com/ep."), None);
+        assert_eq!(url_extension("https://example.com/"), None);
+        assert_eq!(url_extension("not a url"), None);
+    }
+}
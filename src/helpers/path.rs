@@ -6,9 +6,32 @@ use std::path::{Path, PathBuf};
 
 use super::file as file_helper;
 
-/// Get tuifeed configuration directory path.
+/// Name of the directory where the directories of profiles are stored
+const PROFILES_DIR: &str = "profiles";
+/// Name of the default profile
+pub const DEFAULT_PROFILE: &str = "default";
+/// Name of the configuration file
+const CONFIG_FILE: &str = "config.toml";
+
+/// Get tuifeed configuration directory path for `profile`, creating it if it doesn't exist; `None` is the default profile.
+/// Returns None, if it's not possible to get it
+pub fn init_config_dir(profile: Option<&str>) -> Result<Option<PathBuf>, String> {
+    let Some(p) = config_dir(profile)? else {
+        return Ok(None);
+    };
+    // If directory doesn't exist, create it
+    if p.exists() {
+        return Ok(Some(p));
+    }
+    match std::fs::create_dir_all(p.as_path()) {
+        Ok(_) => Ok(Some(p)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Get tuifeed configuration directory path for `profile`, without creating it; `None` is the default profile.
 /// Returns None, if it's not possible to get it
-pub fn init_config_dir() -> Result<Option<PathBuf>, String> {
+pub fn config_dir(profile: Option<&str>) -> Result<Option<PathBuf>, String> {
     // Get file
     #[cfg(not(test))]
     lazy_static! {
@@ -18,28 +41,59 @@ pub fn init_config_dir() -> Result<Option<PathBuf>, String> {
     lazy_static! {
         static ref CONF_DIR: Option<PathBuf> = Some(std::env::temp_dir());
     }
-    if CONF_DIR.is_some() {
-        // Get path of bookmarks
-        let mut p: PathBuf = CONF_DIR.as_ref().unwrap().clone();
+    match CONF_DIR.as_ref() {
         // Append tuifeed dir
-        p.push("tuifeed/");
-        // If directory doesn't exist, create it
-        if p.exists() {
-            return Ok(Some(p));
-        }
-        match std::fs::create_dir(p.as_path()) {
-            Ok(_) => Ok(Some(p)),
-            Err(err) => Err(err.to_string()),
+        Some(dir) => profile_dir(&dir.join("tuifeed/"), profile).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get the directory of `profile` inside the tuifeed directory `base`.
+/// The default profile uses `base` itself, while the others are stored in the `profiles` subdirectory
+pub fn profile_dir(base: &Path, profile: Option<&str>) -> Result<PathBuf, String> {
+    match profile {
+        None | Some(DEFAULT_PROFILE) => Ok(base.to_path_buf()),
+        Some(name) if is_valid_profile_name(name) => Ok(base.join(PROFILES_DIR).join(name)),
+        Some(name) => Err(format!(
+            "Invalid profile name \"{name}\": only letters, digits, '-' and '_' are allowed"
+        )),
+    }
+}
+
+/// List the profiles which have a configuration directory, starting with the default one
+pub fn list_profiles() -> Result<Vec<String>, String> {
+    let mut profiles = Vec::new();
+    if let Some(dir) = config_dir(None)?.map(|x| x.join(PROFILES_DIR))
+        && dir.is_dir()
+    {
+        for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if let Some(name) = path.file_name().and_then(|x| x.to_str())
+                && path.is_dir()
+                && is_valid_profile_name(name)
+            {
+                profiles.push(name.to_string());
+            }
         }
-    } else {
-        Ok(None)
     }
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+
+    Ok(profiles)
+}
+
+/// Returns whether `name` can be used as profile name
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
 }
 
 /// Returns path for config file.
 /// If the file doesn't exist, it will initialize it
 pub fn get_config_file(config_dir: &Path) -> Result<PathBuf, String> {
-    let cfg_file = config_dir.join("config.toml");
+    let cfg_file = config_dir.join(CONFIG_FILE);
     // Check if exists
     if !cfg_file.exists() {
         init_config_file(cfg_file.as_path())?
@@ -47,6 +101,24 @@ pub fn get_config_file(config_dir: &Path) -> Result<PathBuf, String> {
     Ok(cfg_file)
 }
 
+/// Find the configuration file of `profile` without creating anything; `None` is the default profile.
+/// Returns None if the file doesn't exist, and fails if the profile doesn't exist
+pub fn find_config_file(profile: Option<&str>) -> Result<Option<PathBuf>, String> {
+    let Some(config_dir) = config_dir(profile)? else {
+        return Ok(None);
+    };
+    if let Some(name) = profile.filter(|x| *x != DEFAULT_PROFILE)
+        && !config_dir.is_dir()
+    {
+        return Err(format!(
+            "Profile \"{name}\" doesn't exist; run `tuifeed -p {name} -c` to create it"
+        ));
+    }
+    let cfg_file = config_dir.join(CONFIG_FILE);
+
+    Ok(cfg_file.exists().then_some(cfg_file))
+}
+
 /// Initialize configuration file
 fn init_config_file(p: &Path) -> Result<(), String> {
     file_helper::write_file(
@@ -74,11 +146,42 @@ mod tests {
     #[serial]
     fn should_get_config_dir() {
         // Create and get conf_dir
-        let conf_dir: PathBuf = init_config_dir().ok().unwrap().unwrap();
+        let conf_dir: PathBuf = init_config_dir(None).ok().unwrap().unwrap();
         // Remove dir
         assert!(std::fs::remove_dir_all(conf_dir.as_path()).is_ok());
     }
 
+    #[test]
+    #[serial]
+    fn should_get_profile_config_dir() {
+        let conf_dir = init_config_dir(Some("work")).ok().unwrap().unwrap();
+        assert!(conf_dir.ends_with("tuifeed/profiles/work"));
+        assert!(conf_dir.is_dir());
+        assert_eq!(
+            init_config_dir(Some("default")).ok().unwrap(),
+            init_config_dir(None).ok().unwrap()
+        );
+        assert!(init_config_dir(Some("../work")).is_err());
+        assert!(init_config_dir(Some("")).is_err());
+        assert!(std::fs::remove_dir_all(init_config_dir(None).ok().unwrap().unwrap()).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn should_list_profiles() {
+        init_config_dir(Some("work")).ok().unwrap();
+        init_config_dir(Some("personal")).ok().unwrap();
+        assert_eq!(
+            list_profiles().ok().unwrap(),
+            vec![
+                String::from("default"),
+                String::from("personal"),
+                String::from("work")
+            ]
+        );
+        assert!(std::fs::remove_dir_all(init_config_dir(None).ok().unwrap().unwrap()).is_ok());
+    }
+
     #[test]
     #[serial]
     fn should_fail_getting_config_dir() {
@@ -87,6 +190,7 @@ mod tests {
         // Create file
         let mut f: File = OpenOptions::new()
             .create(true)
+            .truncate(true)
             .write(true)
             .open(conf_dir.as_path())
             .ok()
@@ -96,15 +200,27 @@ mod tests {
         // Drop file
         drop(f);
         // Get config dir (will fail)
-        assert!(init_config_dir().is_err());
+        assert!(init_config_dir(None).is_err());
         // Remove file
         assert!(std::fs::remove_file(conf_dir.as_path()).is_ok());
     }
 
+    #[test]
+    #[serial]
+    fn should_find_config_file_without_creating_it() {
+        assert!(find_config_file(Some("typo")).is_err());
+        assert!(!config_dir(Some("typo")).unwrap().unwrap().exists());
+        let conf_dir = init_config_dir(Some("work")).ok().unwrap().unwrap();
+        assert_eq!(find_config_file(Some("work")).unwrap(), None);
+        let cfg_file = get_config_file(conf_dir.as_path()).ok().unwrap();
+        assert_eq!(find_config_file(Some("work")).unwrap(), Some(cfg_file));
+        assert!(std::fs::remove_dir_all(init_config_dir(None).ok().unwrap().unwrap()).is_ok());
+    }
+
     #[test]
     #[serial]
     fn should_get_config_file() {
-        let conf_dir: PathBuf = init_config_dir().ok().unwrap().unwrap();
+        let conf_dir: PathBuf = init_config_dir(None).ok().unwrap().unwrap();
         let cfg_file = get_config_file(conf_dir.as_path()).ok().unwrap();
         assert_eq!(
             format!("{}", cfg_file.display()),
@@ -4,10 +4,20 @@
 
 mod lookup;
 
-use lazy_regex::{Lazy, Regex};
+use lazy_regex::{Captures, Lazy, Regex};
 use unicode_truncate::UnicodeTruncateStr;
+use url::Url;
 
 static HTML_TAG_REGEX: Lazy<Regex> = lazy_regex!(r"<[^>]+>");
+/**
+ * Matches HTML anchors in string
+ *
+ * - group 1: double quoted href (maybe)
+ * - group 2: single quoted href (maybe)
+ * - group 3: anchor inner html
+ */
+static HTML_ANCHOR_REGEX: Lazy<Regex> =
+    lazy_regex!(r#"<a\s[^>]*?href\s*=\s*(?:"([^"]*)"|'([^']*)')[^>]*>(.*?)</a\s*>"#is);
 /**
  * Matches HTML entities in string
  *
@@ -58,6 +68,52 @@ pub fn strip_html(s: &str) -> String {
     escaped
 }
 
+/// Strip html tags and entities from string, replacing each anchor with a numbered footnote
+/// marker (e.g. `[3]`).
+///
+/// Returns the stripped string and the list of links referenced by the markers, where the
+/// link for marker `[n]` is at index `n - 1`. Relative hrefs are resolved against `base`.
+pub fn strip_html_with_links(s: &str, base: &str) -> (String, Vec<String>) {
+    let base = Url::parse(base).ok();
+    let mut links: Vec<String> = Vec::new();
+    let replaced = HTML_ANCHOR_REGEX.replace_all(s, |group: &Captures| {
+        let text = &group[3];
+        let href = group
+            .get(1)
+            .or_else(|| group.get(2))
+            .map_or("", |x| x.as_str());
+        let href = strip_html(href.trim());
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return text.to_string();
+        }
+        let link = resolve_url(&href, base.as_ref());
+        // same link must get the same marker
+        let index = match links.iter().position(|x| x == &link) {
+            Some(index) => index,
+            None => {
+                links.push(link);
+                links.len() - 1
+            }
+        };
+        format!("{}[{}]", text, index + 1)
+    });
+
+    (strip_html(&replaced), links)
+}
+
+/// Resolve `href` against `base`. If the url can't be resolved, `href` is returned as is
+fn resolve_url(href: &str, base: Option<&Url>) -> String {
+    match base {
+        Some(base) => base
+            .join(href)
+            .map(|x| x.to_string())
+            .unwrap_or_else(|_| href.to_string()),
+        None => Url::parse(href)
+            .map(|x| x.to_string())
+            .unwrap_or_else(|_| href.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -100,6 +156,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn should_strip_html_with_links() {
+        let (text, links) = strip_html_with_links(
+            r#"<p>Read <a href="https://example.com/foo">this</a> and <a class="x" href='/bar?a=1&amp;b=2'>that</a>, or <a href="https://example.com/foo">this again</a>.</p>"#,
+            "https://example.com/blog/post",
+        );
+        assert_eq!(text, "Read this[1] and that[2], or this again[1].");
+        assert_eq!(
+            links,
+            vec![
+                "https://example.com/foo".to_string(),
+                "https://example.com/bar?a=1&b=2".to_string()
+            ]
+        );
+        // the other quote character may appear in the href
+        let (text, links) = strip_html_with_links(
+            r#"<a href="https://x.com/it's-here">here</a> <a href='/say"hi"'>hi</a>"#,
+            "",
+        );
+        assert_eq!(text, "here[1] hi[2]");
+        assert_eq!(
+            links,
+            vec![
+                "https://x.com/it's-here".to_string(),
+                "/say\"hi\"".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_relative_links() {
+        let (text, links) = strip_html_with_links(
+            r##"<a href="../other">other</a> <a href="#top">top</a>"##,
+            "https://example.com/blog/post",
+        );
+        assert_eq!(text, "other[1] top");
+        assert_eq!(links, vec!["https://example.com/other".to_string()]);
+        // without a valid base, hrefs are kept as they are
+        let (_, links) = strip_html_with_links(r#"<a href="/foo">foo</a>"#, "");
+        assert_eq!(links, vec!["/foo".to_string()]);
+    }
+
     #[test]
     fn should_replace_multiple_newlines() {
         assert_eq!(
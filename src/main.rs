@@ -1,5 +1,7 @@
 const TUIFEED_VERSION: &str = env!("CARGO_PKG_VERSION");
 const TUIFEED_AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
+/// Environment variable selecting the profile, if `--profile` is not provided
+const TUIFEED_PROFILE_ENV: &str = "TUIFEED_PROFILE";
 
 // -- libs
 #[macro_use]
@@ -8,11 +10,13 @@ extern crate lazy_regex;
 extern crate lazy_static;
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 use argh::FromArgs;
 
 // includes
+mod cli;
 mod config;
 mod feed;
 mod helpers;
@@ -28,8 +32,24 @@ use ui::Ui;
 Please, report issues to <https://github.com/veeso/tuifeed>
 Please, consider supporting the author <https://ko-fi.com/veeso>")]
 struct Args {
-    #[argh(switch, short = 'c', description = "open tuifeed configuration")]
-    config: bool,
+    #[argh(
+        option,
+        description = "use the configuration file at the provided path"
+    )]
+    config: Option<PathBuf>,
+    #[argh(
+        switch,
+        short = 'c',
+        long = "edit-config",
+        description = "open tuifeed configuration"
+    )]
+    edit_config: bool,
+    #[argh(
+        option,
+        short = 'p',
+        description = "use a separate configuration and history; can be set with TUIFEED_PROFILE too"
+    )]
+    profile: Option<String>,
     #[argh(
         option,
         short = 'T',
@@ -39,9 +59,11 @@ struct Args {
     ticks: u64,
     #[argh(switch, short = 'v', description = "print version")]
     version: bool,
+    #[argh(subcommand)]
+    command: Option<cli::Command>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let args: Args = argh::from_env();
     // Print version
     if args.version {
@@ -49,19 +71,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "tuifeed - {} - Developed by {}",
             TUIFEED_VERSION, TUIFEED_AUTHORS
         );
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
+    let profile = args
+        .profile
+        .or_else(|| env::var(TUIFEED_PROFILE_ENV).ok())
+        .filter(|x| !x.is_empty());
+    // subcommands don't create the configuration, so a mistyped profile isn't created
+    let config_path = match args.config {
+        Some(config_file) => Ok(Some(config_file)),
+        None if args.command.is_some() => path_helpers::find_config_file(profile.as_deref()),
+        None => get_config_file(profile.as_deref()),
+    };
+    let config_path = match config_path {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(e.into());
+        }
+    };
     // Open config file
-    if args.config {
-        if let Err(e) = edit_config_file() {
+    if args.edit_config {
+        if let Err(e) = edit_config_file(config_path.as_deref()) {
             eprintln!("{}", e);
             return Err(e.into());
         } else {
-            return Ok(());
+            return Ok(ExitCode::SUCCESS);
         }
     }
+    // Run subcommand
+    if let Some(command) = args.command {
+        return command
+            .run(profile.as_deref(), config_path.as_deref())
+            .inspect_err(|e| eprintln!("{}", e));
+    }
     // Get configuration
-    let config = match init_config() {
+    let config = match init_config(config_path.as_deref()) {
         Ok(cfg) => cfg,
         Err(e) => {
             eprintln!("Failed to initialize configuration: {}", e);
@@ -77,16 +122,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Err("Configuration is empty".into());
     }
     // Run ui
-    Ui::init(config, args.ticks)?.run()
+    Ui::init(config, config_path, profile, args.ticks)?
+        .run()
+        .map(|()| ExitCode::SUCCESS)
 }
 
 /// Edit configuration file
-fn edit_config_file() -> Result<(), String> {
-    let Some(p) = get_config_file() else {
+fn edit_config_file(p: Option<&Path>) -> Result<(), String> {
+    let Some(p) = p else {
         return Err("Could not find a configuration path on your operating system...".to_string());
     };
 
-    if let Err(e) = open_helpers::open_text_file(p.as_path()) {
+    if let Err(e) = open_helpers::open_text_file(p) {
         eprintln!();
         Err(format!("Could not open configuration file: {}", e))
     } else {
@@ -94,28 +141,22 @@ fn edit_config_file() -> Result<(), String> {
     }
 }
 
-/// Initialize configuration
-fn init_config() -> Result<Config, String> {
-    let config_dir = path_helpers::init_config_dir()?;
-    let Some(config_dir) = config_dir else {
+/// Initialize configuration from the file at `config_file`
+pub(crate) fn init_config(config_file: Option<&Path>) -> Result<Config, String> {
+    let Some(config_file) = config_file else {
         return Ok(Config::default());
     };
-
-    // if config dir doesn't exist create it
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
-    }
-
-    let config_file = path_helpers::get_config_file(&config_dir)?;
     // Parse configuration
-    let config_file =
-        file_helpers::open_file_read(config_file.as_path()).map_err(|e| e.to_string())?;
-    config_serializer::deserialize(config_file).map_err(|e| e.to_string())
+    let reader = file_helpers::open_file_read(config_file)
+        .map_err(|e| format!("{}: {}", config_file.display(), e))?;
+    config_serializer::deserialize(reader).map_err(|e| e.to_string())
 }
 
-/// Get configuration file path
-fn get_config_file() -> Option<PathBuf> {
-    let config_dir = path_helpers::init_config_dir().ok()??;
+/// Get the configuration file path of `profile`, which is initialized if it doesn't exist
+fn get_config_file(profile: Option<&str>) -> Result<Option<PathBuf>, String> {
+    let Some(config_dir) = path_helpers::init_config_dir(profile)? else {
+        return Ok(None);
+    };
 
-    path_helpers::get_config_file(config_dir.as_path()).ok()
+    path_helpers::get_config_file(config_dir.as_path()).map(Some)
 }
@@ -6,33 +6,58 @@ mod components;
 mod lib;
 mod view;
 
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
-use lib::{FeedClient, FeedState, FlatFeedState, History, Kiosk};
+use chrono::{DateTime, Local};
+use lib::{
+    Download, DownloadStatus, Downloader, Downloads, Duplicates, ErrorLog, FeedClient,
+    FlatFeedState, HistorySync, MERGED_FEED_NAME, StatusMessage, downloads, find_unread,
+};
+pub use lib::{FeedState, History, Kiosk, Retention};
+use tuirealm::props::{PropPayload, PropValue};
 use tuirealm::terminal::{CrosstermTerminalAdapter, TerminalBridge};
 use tuirealm::{
     Application, AttrValue, Attribute, NoUserEvent, PollStrategy, State, StateValue, Update,
 };
 
-use crate::config::Config;
-use crate::feed::{Feed, FeedSource};
-use crate::helpers::open as open_helpers;
+use crate::config::serializer::{self as config_serializer, SourceChange};
+use crate::config::{Config, OpenerConfig};
+use crate::feed::{Article, Feed, FeedError, FeedSource};
+use crate::helpers::{clipboard as clipboard_helpers, file as file_helpers, open as open_helpers};
 
 const FORCED_REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+const RELATIVE_DATES_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const HISTORY_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+const HISTORY_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+const DUPLICATES_UPDATE_INTERVAL: Duration = Duration::from_secs(2);
 
 /// identifiers for components
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub enum Id {
     ArticleAuthors,
     ArticleDate,
+    ArticleEnclosure,
     ArticleLink,
     ArticleList,
     ArticleSummary,
     ArticleTitle,
+    DownloadQueue,
+    ErrorLog,
     ErrorPopup,
     FeedList,
     GlobalListener,
+    LinkPicker,
     QuitPopup,
+    RemoveSourcePopup,
+    SourceFormName,
+    SourceFormUrl,
+    Stats,
+    StatusBar,
 }
 
 /// Messages produced by components
@@ -41,18 +66,49 @@ pub enum Msg {
     ArticleBlur,
     ArticleChanged(usize),
     ArticleListBlur,
+    ArticleScrolledToEnd,
+    ClearErrorLog,
+    CloseDownloadQueue,
     CloseErrorPopup,
+    CloseLinkPicker,
     CloseQuitPopup,
+    CloseRemoveSourcePopup,
+    CloseSourceForm,
+    CopyArticleMarkdownLink,
+    CopyArticleText,
+    CopyArticleUrl,
+    DownloadEnclosure,
     FeedChanged(usize),
     FeedListBlur,
     FetchAllSources,
     FetchSource,
     MarkSourceAsRead,
+    MarkSourceAsUnread,
     MarkAllSourcesAsRead,
     GoReadArticle,
+    GoToNextUnread,
+    GoToPreviousUnread,
     OpenArticle,
+    OpenArticleLink(usize),
+    PlayEnclosure,
     Quit,
+    ReadInPager,
+    ReloadConfig,
+    RemoveDownload(usize),
+    RemoveSource,
+    RetryDownload(usize),
+    RetryErrorSource(usize),
+    ShowAddSourceForm,
+    ShowEditSourceForm,
+    ShowLinkPicker,
     ShowQuitPopup,
+    ShowRemoveSourcePopup,
+    SourceFormBlur,
+    SubmitSourceForm,
+    ToggleDownloadQueue,
+    ToggleArticleRead,
+    ToggleErrorLog,
+    ToggleStats,
     /// No-op
     None,
 }
@@ -62,36 +118,136 @@ pub struct Ui {
     application: Application<Id, Msg, NoUserEvent>,
     client: FeedClient,
     config: Config,
+    /// Last modification time of the configuration file
+    config_modified: Option<SystemTime>,
+    /// Path of the configuration file, where changes to sources are written
+    config_path: Option<PathBuf>,
+    downloader: Downloader,
+    downloads: Downloads,
+    /// Duplicate articles across sources and the feed where they are merged
+    duplicates: Duplicates,
+    /// Whether feeds have been fetched since the duplicate articles were found
+    duplicates_outdated: bool,
+    error_log: ErrorLog,
     history: History,
+    /// Synchronization of the history through a shared directory, if configured
+    history_sync: Option<HistorySync>,
+    /// Set on SIGINT, which terminates tuifeed; SIGINTs received while the ui is suspended are meant for the program
+    /// using the terminal, so they are discarded
+    interrupt: Arc<AtomicBool>,
     kiosk: Kiosk,
+    /// Last time the configuration file was checked for changes
+    last_config_check: Instant,
+    /// Last time the duplicate articles were found
+    last_duplicates_update: Instant,
+    /// Last time the history was saved
+    last_history_save: Instant,
+    /// Last time the history was synchronized
+    last_history_sync: Instant,
+    /// Time of the last full refresh
+    last_refresh: Option<DateTime<Local>>,
     last_redraw: Instant,
+    /// Last time relative dates were updated
+    last_relative_dates_refresh: Instant,
+    /// Source change waiting for the test fetch to succeed before being saved
+    pending_source: Option<SourceChange>,
+    /// Name of the profile in use; `None` is the default profile
+    profile: Option<String>,
     redraw: bool,
+    /// Whether a full refresh is in progress
+    refreshing: bool,
+    /// Name of the source being edited in the source form; `None` if adding a new source
+    source_form: Option<String>,
+    status_message: Option<StatusMessage>,
+    /// Set when tuifeed must terminate (e.g. on SIGTERM or when the terminal is closed)
+    terminate: Arc<AtomicBool>,
     terminal: TerminalBridge<CrosstermTerminalAdapter>,
+    /// Amount of unread articles across all the fetched sources; `None` if it must be counted again,
+    /// because articles have been fetched or their read state has changed
+    unread_count: Option<usize>,
 }
 
 impl Ui {
     /// Init a new [`Ui`] instance
-    pub fn init(config: Config, ticks: u64) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn init(
+        config: Config,
+        config_path: Option<PathBuf>,
+        profile: Option<String>,
+        ticks: u64,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut terminal = TerminalBridge::init_crossterm()?;
         let _ = terminal.disable_mouse_capture();
 
-        let history_path = History::default_path()?;
-        let history = History::load(&history_path)?;
+        let history_path = History::default_path(profile.as_deref())?;
+        let mut history = History::load(&history_path)?;
+        history.set_source_identities(config.source_identities());
+        history.set_retention(Retention::from(&config.history.clone().unwrap_or_default()));
+        history.drop_removed_sources();
+        let history_sync = match config.history.as_ref().and_then(|x| x.sync_dir.as_deref()) {
+            Some(dir) => Some(HistorySync::open(dir, profile.as_deref())?),
+            None => None,
+        };
+        let mut downloads = Downloads::load(&Downloads::default_path(profile.as_deref())?)?;
+        downloads.set_source_identities(config.source_identities());
 
         let mut kiosk = Kiosk::default();
         for name in config.sources.keys() {
             kiosk.insert_feed(name, FeedState::Loading);
         }
-        Ok(Self {
-            application: Self::init_application(&kiosk, Duration::from_millis(ticks)),
+        let terminate = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        for signal in [signal_hook::consts::SIGHUP, signal_hook::consts::SIGTERM] {
+            let _ = signal_hook::flag::register(signal, terminate.clone());
+        }
+        // in raw mode CTRL+C doesn't send SIGINT, so it only comes from other processes (e.g. `kill -INT`)
+        let interrupt = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        let _ = signal_hook::flag::register(signal_hook::consts::SIGINT, interrupt.clone());
+        let mut ui = Self {
+            application: Self::init_application(
+                &kiosk,
+                config.merged_view(),
+                Duration::from_millis(ticks),
+            ),
             client: FeedClient::default(),
             config,
+            config_modified: config_path.as_deref().and_then(Self::modified_time),
+            config_path,
+            downloader: Downloader::default(),
+            downloads,
+            duplicates: Duplicates::default(),
+            duplicates_outdated: false,
+            error_log: ErrorLog::default(),
             history,
+            history_sync,
+            interrupt,
             kiosk,
+            last_config_check: Instant::now(),
+            last_history_save: Instant::now(),
+            last_history_sync: Instant::now(),
+            last_refresh: None,
             last_redraw: Instant::now(),
+            last_relative_dates_refresh: Instant::now(),
+            last_duplicates_update: Instant::now(),
+            pending_source: None,
+            profile,
             redraw: true,
+            refreshing: false,
+            source_form: None,
+            status_message: None,
+            terminate,
             terminal,
-        })
+            unread_count: None,
+        };
+        if let Some(warning) = ui.history.take_warning() {
+            ui.mount_error_popup(warning);
+        }
+        if let Some(warning) = ui.downloads.take_warning() {
+            ui.mount_error_popup(warning);
+        }
+        ui.sync_history();
+
+        Ok(ui)
     }
 
     /// run the ui
@@ -101,6 +257,9 @@ impl Ui {
         let mut quit = false;
         // Main loop
         while !quit {
+            if self.terminate.load(Ordering::Relaxed) || self.interrupt.load(Ordering::Relaxed) {
+                break;
+            }
             // poll and update
             match self.application.tick(PollStrategy::UpTo(3)) {
                 Ok(messages) if messages.is_empty() => {}
@@ -119,6 +278,18 @@ impl Ui {
             }
             // Poll fetched sources
             self.poll_fetched_sources();
+            // Find duplicates among the fetched sources
+            self.check_duplicates_outdated();
+            // Poll downloads and start queued ones
+            self.process_downloads();
+            // Reload configuration if changed
+            self.check_config_changed();
+            // Save history if changed
+            self.autosave_history();
+            // Sync history with other machines
+            self.check_history_sync();
+            // Update relative dates
+            self.refresh_relative_dates();
             // Check whether to force redraw
             self.check_force_redraw();
             // View
@@ -129,6 +300,7 @@ impl Ui {
 
         // save history
         self.history.save()?;
+        self.downloads.save()?;
 
         Ok(())
     }
@@ -140,11 +312,12 @@ impl Ui {
             .config
             .sources
             .iter()
-            .map(|(name, uri)| (name.clone(), uri.clone()))
+            .map(|(name, config)| (name.clone(), config.source.clone()))
             .collect();
         for (name, source) in sources.into_iter() {
             self.fetch_source(name.as_str(), source);
         }
+        self.refreshing = true;
     }
 
     /// Start a worker to fetch sources
@@ -152,11 +325,7 @@ impl Ui {
         self.client.fetch(name, &source);
         // Mark source as Loading
         self.update_source(name, FeedState::Loading);
-        self.update_feed_list_item(
-            name,
-            FlatFeedState::Loading,
-            self.history.is_source_read(name),
-        );
+        self.update_feed_list_item(name, FlatFeedState::Loading, self.is_source_read(name));
         // Force redraw
         self.redraw = true;
     }
@@ -164,17 +333,32 @@ impl Ui {
     /// Update source in kiosk
     fn update_source(&mut self, name: &str, state: FeedState) {
         self.kiosk.insert_feed(name, state);
+        self.unread_count = None;
     }
 
     /// Get result for all fetched sources
     fn poll_fetched_sources(&mut self) {
-        if let Some((name, result)) = self.client.poll() {
+        if let Some((name, source, result)) = self.client.poll() {
+            // Complete the validation of a new or edited source; a refresh of the source being edited isn't its test fetch
+            if let Some(change) = self
+                .pending_source
+                .take_if(|x| x.name() == name && x.source() == Some(&source))
+                && !self.complete_pending_source(change, result.as_ref().err())
+            {
+                self.check_refresh_terminated();
+                return;
+            }
+            // Ignore the feed of a source which has been removed in the meantime
+            if !self.config.sources.contains_key(&name) {
+                self.check_refresh_terminated();
+                return;
+            }
             // Adapt state
             let state = match result {
                 Ok(feed) => FeedState::Success(feed),
                 Err(err) => {
-                    // Mount error and return err
-                    self.mount_error_popup(format!(r#"Could not fetch feed "{}": {}"#, name, err));
+                    // Log error and return err
+                    self.log_error(&name, err.clone());
                     FeedState::Error(err)
                 }
             };
@@ -184,42 +368,292 @@ impl Ui {
                 // filter articlesd
                 let articles: Vec<_> = feed.articles().collect();
                 self.history.filter_articles(name.as_str(), &articles);
+                let updates_unread = self.config.updates_unread(&name);
                 for article in articles {
-                    self.history.insert(&feed.name, article);
+                    self.history.insert(&feed.name, article, updates_unread);
                 }
             }
 
             // Update source
             let flat_state = FlatFeedState::from(&state);
             self.update_source(name.as_str(), state);
+            self.duplicates_outdated = true;
             // Update feed list and initialize article
-            self.update_feed_list_item(
-                name.as_str(),
-                flat_state,
-                self.history.is_source_read(&name),
-            );
-            let selected_feed = self.get_selected_feed();
-            if self.is_article_list_empty() && selected_feed.is_some() {
+            self.update_feed_list_item(name.as_str(), flat_state, self.is_source_read(&name));
+            if self.is_article_list_empty()
+                && let Some(selected_feed) = self.get_selected_feed()
+            {
                 let article_list = self.get_article_list(
                     &self.config,
-                    selected_feed.expect("selected feed cannot be none"),
+                    selected_feed,
                     &self.history,
                     self.max_article_name_len(),
                     None,
                 );
                 self.init_article(article_list);
             }
+            self.check_refresh_terminated();
             // Force redraw
             self.redraw = true;
         }
     }
 
+    /// Check whether the full refresh has terminated
+    fn check_refresh_terminated(&mut self) {
+        if self.refreshing && !self.client.running() {
+            self.refreshing = false;
+            self.last_refresh = Some(Local::now());
+        }
+    }
+
+    /// Push a fetch error to the error log
+    fn log_error(&mut self, name: &str, err: FeedError) {
+        self.set_status_message(format!(r#"Could not fetch feed "{name}""#));
+        self.error_log.push(name, err);
+        if self.application.mounted(&Id::ErrorLog) {
+            self.update_error_log();
+        }
+    }
+
+    /// Fetch again the source of the error at `index` in the error log
+    fn retry_error_source(&mut self, index: usize) {
+        let Some(name) = self.error_log.get(index).map(|x| x.source.clone()) else {
+            return;
+        };
+        if let Some(source) = self.config.sources.get(&name).map(|x| x.source.clone()) {
+            self.fetch_source(&name, source);
+            self.set_status_message(format!(r#"Fetching "{name}"…"#));
+        }
+    }
+
+    /// Show a transient message in the status bar
+    fn set_status_message(&mut self, text: impl ToString) {
+        self.status_message = Some(StatusMessage::new(text));
+        self.redraw = true;
+    }
+
+    /// Returns the amount of unread articles across all the fetched sources.
+    /// The amount is counted again only if it has been invalidated
+    fn unread_count(&mut self) -> usize {
+        if let Some(count) = self.unread_count {
+            return count;
+        }
+        let count = self
+            .kiosk
+            .sources()
+            .into_iter()
+            .filter_map(|name| self.kiosk.get_feed(name))
+            .map(|feed| {
+                feed.articles()
+                    .filter(|article| !self.history.is_article_read(&feed.name, article))
+                    .count()
+            })
+            .sum();
+        self.unread_count = Some(count);
+        count
+    }
+
     /// Check whether should force redraw
     fn check_force_redraw(&mut self) {
         // If source are loading and at least 100ms has elapsed since last redraw...
-        if self.client.running() && self.since_last_redraw() >= FORCED_REDRAW_INTERVAL {
+        let downloading =
+            self.downloader.running() > 0 && self.application.mounted(&Id::DownloadQueue);
+        if (self.client.running() || downloading)
+            && self.since_last_redraw() >= FORCED_REDRAW_INTERVAL
+        {
             self.redraw = true;
         }
+        // clear expired status message
+        if self
+            .status_message
+            .as_ref()
+            .is_some_and(|message| message.is_expired())
+        {
+            self.status_message = None;
+            self.redraw = true;
+        }
+    }
+
+    /// Reload the configuration if the configuration file has changed
+    fn check_config_changed(&mut self) {
+        if self.last_config_check.elapsed() < CONFIG_CHECK_INTERVAL {
+            return;
+        }
+        self.last_config_check = Instant::now();
+        let modified = self.config_path.as_deref().and_then(Self::modified_time);
+        if modified != self.config_modified {
+            self.config_modified = modified;
+            self.reload_config();
+        }
+    }
+
+    /// Save the history if it has changed since the last save, at most once per [`HISTORY_AUTOSAVE_INTERVAL`]
+    fn autosave_history(&mut self) {
+        if !self.history.is_dirty() || self.last_history_save.elapsed() < HISTORY_AUTOSAVE_INTERVAL
+        {
+            return;
+        }
+        self.last_history_save = Instant::now();
+        if let Err(err) = self.history.save() {
+            self.set_status_message(format!("Could not save history: {err}"));
+        }
+        if let Some(sync) = self.history_sync.as_mut() {
+            sync.mark_changed();
+        }
+    }
+
+    /// Sync history, at most once per [`HISTORY_SYNC_INTERVAL`]
+    fn check_history_sync(&mut self) {
+        if self.last_history_sync.elapsed() >= HISTORY_SYNC_INTERVAL {
+            self.sync_history();
+        }
+    }
+
+    /// Merge the history of the other machines and export ours to the sync directory.
+    /// If the read state of any article has changed, lists are updated
+    fn sync_history(&mut self) {
+        self.last_history_sync = Instant::now();
+        let Some(sync) = self.history_sync.as_mut() else {
+            return;
+        };
+        let result = sync.sync(&mut self.history);
+        if let Some(warning) = sync.take_warning() {
+            self.set_status_message(warning);
+        }
+        match result {
+            Ok(0) => {}
+            Ok(count) => {
+                self.unread_count = None;
+                if let Some(feed) = self.get_selected_feed().cloned() {
+                    let selected_line = self.get_selected_article_index();
+                    self.reload_article_list(&feed, selected_line);
+                }
+                self.update_feed_list_items();
+                self.set_status_message(format!("Synced read state of {count} articles"));
+            }
+            Err(err) => {
+                self.set_status_message(format!("Could not sync history: {err}"));
+            }
+        }
+    }
+
+    /// Get the modification time of the file at `path`
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|x| x.modified()).ok()
+    }
+
+    /// Read the configuration file again and apply the changes.
+    /// If the configuration is invalid, the current one is kept
+    fn reload_config(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            return;
+        };
+        let config: Config = match file_helpers::open_file_read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|reader| config_serializer::deserialize(reader).map_err(|e| e.to_string()))
+        {
+            Ok(config) => config,
+            Err(err) => {
+                self.set_status_message(format!("Could not reload configuration: {err}"));
+                return;
+            }
+        };
+        // a configuration without sources is refused, as at startup
+        if config.sources.is_empty() {
+            self.set_status_message("Could not reload configuration: Configuration is empty");
+            return;
+        }
+        let diff = self.config.diff_sources(&config);
+        let merged_view_changed = self.config.merged_view() != config.merged_view();
+        self.config = config;
+        self.unread_count = None;
+        self.history
+            .set_source_identities(self.config.source_identities());
+        self.downloads
+            .set_source_identities(self.config.source_identities());
+        self.history.set_retention(Retention::from(
+            &self.config.history.clone().unwrap_or_default(),
+        ));
+        // the history of removed sources is kept, in case they're added back, until it exceeds the retention policy
+        self.history.drop_removed_sources();
+        let sync_dir = self
+            .config
+            .history
+            .as_ref()
+            .and_then(|x| x.sync_dir.clone());
+        if sync_dir.as_deref() != self.history_sync.as_ref().map(|x| x.dir()) {
+            let profile = self.profile.as_deref();
+            self.history_sync = match sync_dir
+                .as_deref()
+                .map(|dir| HistorySync::open(dir, profile))
+                .transpose()
+            {
+                Ok(sync) => sync,
+                Err(err) => {
+                    self.set_status_message(format!("Could not sync history: {err}"));
+                    None
+                }
+            };
+            self.sync_history();
+        }
+        let selected = self.get_selected_feed_name();
+        // unmount removed sources
+        for name in diff.removed.iter() {
+            self.pending_source.take_if(|x| x.name() == name);
+            self.kiosk.remove_feed(name);
+        }
+        for name in diff.added.iter() {
+            self.kiosk.insert_feed(name, FeedState::Loading);
+        }
+        if !diff.added.is_empty() || !diff.removed.is_empty() || merged_view_changed {
+            self.remount_feed_list(selected.as_deref());
+        }
+        self.update_duplicates();
+        // fetch new and changed sources
+        for name in diff.added.iter().chain(diff.changed.iter()) {
+            if let Some(source) = self.config.sources.get(name).map(|x| x.source.clone()) {
+                self.fetch_source(name, source);
+            }
+        }
+        // apply display options
+        match self.get_selected_feed().cloned() {
+            Some(feed) => {
+                let selected_line = if self.get_selected_feed_name() == selected {
+                    self.get_selected_article_index()
+                } else {
+                    None
+                };
+                self.reload_article_list(&feed, selected_line);
+                self.update_article(selected_line.unwrap_or_default());
+            }
+            None => self.clear_article_list(),
+        }
+        if diff.is_empty() {
+            self.set_status_message("Configuration reloaded");
+        } else {
+            self.set_status_message(format!(
+                "Configuration reloaded: {} added, {} changed, {} removed",
+                diff.added.len(),
+                diff.changed.len(),
+                diff.removed.len()
+            ));
+        }
+    }
+
+    /// Update article list and article date if dates are relative, so they don't get stale
+    fn refresh_relative_dates(&mut self) {
+        if !self.config.has_relative_dates()
+            || self.last_relative_dates_refresh.elapsed() < RELATIVE_DATES_REFRESH_INTERVAL
+        {
+            return;
+        }
+        self.last_relative_dates_refresh = Instant::now();
+        if let Some(feed) = self.get_selected_feed().cloned() {
+            let selected_line = self.get_selected_article_index();
+            self.reload_article_list(&feed, selected_line);
+        }
+        self.update_article_date();
+        self.redraw = true;
     }
 
     /// return last redraw
@@ -227,17 +661,145 @@ impl Ui {
         self.last_redraw.elapsed()
     }
 
-    /// Get sorted sources from kiosk
+    /// Get sorted sources from kiosk; the merged feed, if enabled, comes first
     fn sorted_sources(&self) -> Vec<&String> {
         let mut sources = self.kiosk.sources();
         sources.sort();
+        if self.config.merged_view() {
+            sources.insert(0, &self.duplicates.merged_feed().name);
+        }
         sources
     }
 
+    /// Get the feed of the source `name`, which may be the merged feed
+    fn get_feed(&self, name: &str) -> Option<&Feed> {
+        if self.is_merged_feed(name) {
+            Some(self.duplicates.merged_feed())
+        } else {
+            self.kiosk.get_feed(name)
+        }
+    }
+
     /// Get currently selected feed
     fn get_selected_feed(&self) -> Option<&Feed> {
         let feed = self.get_selected_feed_name()?;
-        self.kiosk.get_feed(feed.as_str())
+        self.get_feed(feed.as_str())
+    }
+
+    /// Returns whether `name` is the feed where the articles of all sources are merged
+    fn is_merged_feed(&self, name: &str) -> bool {
+        self.config.merged_view() && name == MERGED_FEED_NAME
+    }
+
+    /// Get the articles the article at `index` of `feed` comes from, as source name and article.
+    /// An article of the merged feed comes from each source which published it; the first one is the displayed one
+    fn article_sources<'a>(&'a self, feed: &'a Feed, index: usize) -> Vec<(&'a str, &'a Article)> {
+        if !self.is_merged_feed(&feed.name) {
+            return feed
+                .articles()
+                .nth(index)
+                .map(|article| (feed.name.as_str(), article))
+                .into_iter()
+                .collect();
+        }
+        self.duplicates
+            .merged_sources(index)
+            .iter()
+            .filter_map(|(source, id)| {
+                self.kiosk
+                    .get_feed(source)?
+                    .articles()
+                    .find(|x| &x.id == id)
+                    .map(|article| (source.as_str(), article))
+            })
+            .collect()
+    }
+
+    /// Get the source and the article displayed at `index` of `feed`
+    fn article_source(&self, feed: &Feed, index: usize) -> Option<(String, Article)> {
+        self.article_sources(feed, index)
+            .first()
+            .map(|(source, article)| (source.to_string(), (*article).clone()))
+    }
+
+    /// Returns whether all the articles of the source `name` have been read.
+    /// The merged feed is read when all the fetched sources are read
+    fn is_source_read(&self, name: &str) -> bool {
+        if !self.is_merged_feed(name) {
+            return self.history.is_source_read(name);
+        }
+        self.kiosk
+            .sources()
+            .into_iter()
+            .filter(|source| self.kiosk.get_feed(source).is_some())
+            .all(|source| self.history.is_source_read(source))
+    }
+
+    /// Find the duplicate articles again once the running fetches have terminated,
+    /// or every [`DUPLICATES_UPDATE_INTERVAL`] while they're running, so a refresh doesn't compare articles for each source
+    fn check_duplicates_outdated(&mut self) {
+        if self.duplicates_outdated
+            && (!self.client.running()
+                || self.last_duplicates_update.elapsed() >= DUPLICATES_UPDATE_INTERVAL)
+        {
+            self.update_duplicates();
+            self.redraw = true;
+        }
+    }
+
+    /// Find the duplicate articles again after the feeds have changed, then update the merged feed
+    fn update_duplicates(&mut self) {
+        self.duplicates_outdated = false;
+        self.last_duplicates_update = Instant::now();
+        let config = self.config.duplicates.clone().unwrap_or_default();
+        let merged_view = self.config.merged_view();
+        if config.match_by.is_empty() && !merged_view {
+            self.duplicates = Duplicates::default();
+            return;
+        }
+        // keep the selected article of the merged feed selected
+        let selected = self
+            .get_selected_feed_name()
+            .filter(|name| self.is_merged_feed(name))
+            .and_then(|_| self.get_selected_article_index())
+            .and_then(|index| self.duplicates.merged_sources(index).first().cloned());
+        self.duplicates = Duplicates::new(&config, &self.kiosk);
+        if !merged_view {
+            return;
+        }
+        let state = self.merged_feed_state();
+        self.update_feed_list_item(
+            MERGED_FEED_NAME,
+            state,
+            self.is_source_read(MERGED_FEED_NAME),
+        );
+        if let Some(selected) = selected {
+            let feed = self.duplicates.merged_feed().clone();
+            let index = (0..feed.articles.len())
+                .find(|i| self.duplicates.merged_sources(*i).contains(&selected));
+            self.reload_article_list(&feed, index);
+        }
+    }
+
+    /// Get the state of the merged feed, which is loading while any source is loading
+    fn merged_feed_state(&self) -> FlatFeedState {
+        if self.kiosk.count(FlatFeedState::Loading) > 0 {
+            FlatFeedState::Loading
+        } else {
+            FlatFeedState::Success
+        }
+    }
+
+    /// Update the state of all the items of the feed list
+    fn update_feed_list_items(&mut self) {
+        let mut items = self.kiosk.get_state();
+        if self.config.merged_view() {
+            items.push((MERGED_FEED_NAME.to_string(), self.merged_feed_state()));
+        }
+        for (name, state) in items {
+            let read = self.is_source_read(&name);
+            self.update_feed_list_item(&name, state, read);
+        }
     }
 
     /// Get currently selected feed name
@@ -250,37 +812,378 @@ impl Ui {
         self.sorted_sources().get(feed).cloned().cloned()
     }
 
-    /// mark article as viewed in history
-    fn mark_viewed_article(&mut self, index: usize) {
-        let Some(feed_name) = self.get_selected_feed_name() else {
+    /// Get currently selected article
+    fn get_selected_article(&self) -> Option<&Article> {
+        let index = self.get_selected_article_index()?;
+
+        self.get_selected_feed()?.articles().nth(index)
+    }
+
+    /// Get index of the currently selected article
+    fn get_selected_article_index(&self) -> Option<usize> {
+        let State::One(StateValue::Usize(index)) = self.application.state(&Id::ArticleList).ok()?
+        else {
+            return None;
+        };
+
+        Some(index)
+    }
+
+    /// Open `url` with the first matching opener; if no opener matches, the url is opened with the default application.
+    /// If the MIME type of the resource is unknown, it is guessed from the url
+    fn open_url(&mut self, url: &str, mime: Option<&str>) -> Result<(), String> {
+        let mime = mime.or_else(|| open_helpers::guess_mime(url));
+        let opener = self.config.opener(url, mime).cloned();
+        self.open_with(opener, url)
+    }
+
+    /// Open `url` with `opener`, or with the default application if there's none
+    fn open_with(&mut self, opener: Option<OpenerConfig>, url: &str) -> Result<(), String> {
+        match opener {
+            Some(opener) if opener.terminal => {
+                self.suspend(|| open_helpers::run_in_terminal(&opener.command, url))
+            }
+            Some(opener) => open_helpers::open_with(&opener.command, url),
+            None => open_helpers::open_link(url),
+        }
+    }
+
+    /// Copy `text` to the clipboard through OSC 52, then through the configured clipboard command if any.
+    /// Whether the terminal supports OSC 52 can't be known, so the command is run as fallback even if the sequence
+    /// has been written
+    fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        let config = self.config.clipboard.clone().unwrap_or_default();
+        let osc52 = config
+            .osc52
+            .unwrap_or(true)
+            .then(|| clipboard_helpers::copy_osc52(text));
+        let command = config
+            .command
+            .as_deref()
+            .map(|command| clipboard_helpers::copy_with(command, text));
+        let result = match (osc52, command) {
+            (None, None) => Err(String::from("No clipboard available")),
+            (Some(Ok(())), _) | (_, Some(Ok(()))) => Ok(()),
+            (_, Some(Err(err))) | (Some(Err(err)), None) => Err(err),
+        };
+        match result {
+            Ok(()) => self.set_status_message(format!("{what} copied to clipboard")),
+            Err(err) => self.set_status_message(err),
+        }
+    }
+
+    /// Read the currently selected article in the configured external reader, then mark it as read
+    fn read_in_pager(&mut self) {
+        let Some(index) = self.get_selected_article_index() else {
+            return;
+        };
+        let Some(text) = self
+            .get_selected_article()
+            .map(|article| self.article_text(article))
+        else {
+            return;
+        };
+        let command = self.config.reader_command();
+        let result = self.suspend(|| open_helpers::pipe_to(&command, &text));
+        self.mark_viewed_article(index, false);
+        if let Err(err) = result {
+            self.set_status_message(err);
+        }
+    }
+
+    /// Suspend the terminal ui while running `f`, which can use the terminal.
+    /// The terminal is always restored after `f` returns
+    fn suspend<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        // stop the event listener, so it doesn't steal the input from the program
+        let _ = self.application.lock_ports();
+        let _ = self.terminal.leave_alternate_screen();
+        let _ = self.terminal.disable_raw_mode();
+        let result = f();
+        // the terminal sent SIGINT on CTRL+C while `f` was running: it was meant for the program, so it must not
+        // terminate tuifeed
+        self.interrupt.store(false, Ordering::Relaxed);
+        let _ = self.terminal.enable_raw_mode();
+        let _ = self.terminal.enter_alternate_screen();
+        // clear the terminal buffers to force a full redraw
+        let _ = self.terminal.raw_mut().clear();
+        let _ = self.application.unlock_ports();
+        self.redraw = true;
+
+        result
+    }
+
+    /// Open the link at `index` of the currently selected article
+    fn open_article_link(&mut self, index: usize) {
+        let Some(link) = self
+            .get_selected_article()
+            .and_then(|article| article.links.get(index))
+            .cloned()
+        else {
+            return;
+        };
+        match self.open_url(link.as_str(), None) {
+            Ok(()) => self.set_status_message("Link opened"),
+            Err(err) => self.mount_error_popup(err),
+        }
+    }
+
+    /// Open the first enclosure of the currently selected article with the configured media player
+    fn play_enclosure(&mut self) {
+        let Some(enclosure) = self
+            .get_selected_article()
+            .and_then(|article| article.enclosures.first())
+            .cloned()
+        else {
+            self.set_status_message("No media attached to this article");
             return;
         };
+        let mime = enclosure
+            .mime
+            .as_deref()
+            .or_else(|| open_helpers::guess_mime(&enclosure.url));
+        let opener = self.config.media_opener(&enclosure.url, mime);
+        let result = self.open_with(opener, &enclosure.url);
+        match result {
+            Ok(()) => self.set_status_message("Playing media"),
+            Err(err) => self.mount_error_popup(err),
+        }
+    }
+
+    /// Enqueue the download of the first enclosure of the currently selected article
+    fn download_enclosure(&mut self) {
+        let Some(feed) = self.get_selected_feed() else {
+            return;
+        };
+        let Some(index) = self.get_selected_article_index() else {
+            return;
+        };
+        let Some((source, article)) = self.article_sources(feed, index).first().copied() else {
+            return;
+        };
+        let Some(enclosure) = article.enclosures.first() else {
+            self.set_status_message("No media attached to this article");
+            return;
+        };
+        let config = self.config.downloads.clone().unwrap_or_default();
+        let identity = self
+            .config
+            .sources
+            .get(source)
+            .map(|x| x.identity())
+            .unwrap_or_else(|| source.to_string());
+        let download = Download::new(&config, source, &identity, article, &enclosure.url);
+        let title = download.title.clone();
+        if self.downloads.enqueue(download) {
+            self.save_downloads();
+            self.set_status_message(format!("Queued download of \"{title}\""));
+        } else {
+            self.set_status_message(format!("\"{title}\" is already queued or downloaded"));
+        }
+    }
+
+    /// Poll terminated downloads and start the queued ones
+    fn process_downloads(&mut self) {
+        let mut changed = false;
+        while let Some((url, result)) = self.downloader.poll() {
+            let status = match result {
+                Ok(()) => DownloadStatus::Completed,
+                Err(err) => DownloadStatus::Failed(err),
+            };
+            self.downloads.set_status(&url, status);
+            changed = true;
+        }
+        let max_workers =
+            downloads::max_workers(&self.config.downloads.clone().unwrap_or_default());
+        while self.downloader.running() < max_workers {
+            let Some(download) = self.downloads.next_queued().cloned() else {
+                break;
+            };
+            self.downloader.download(&download.url, &download.path);
+            self.downloads
+                .set_status(&download.url, DownloadStatus::Running);
+            changed = true;
+        }
+
+        if changed {
+            self.save_downloads();
+            // update downloaded markers
+            if let Some(feed) = self.get_selected_feed().cloned() {
+                let selected_line = self.get_selected_article_index();
+                self.reload_article_list(&feed, selected_line);
+            }
+        }
+        if self.application.mounted(&Id::DownloadQueue)
+            && (changed || self.downloader.running() > 0)
+        {
+            self.update_download_queue();
+        }
+    }
+
+    /// Retry the download at `index` in the queue
+    fn retry_download(&mut self, index: usize) {
+        let Some(download) = self.downloads.get(index).cloned() else {
+            return;
+        };
+        if self.downloads.enqueue(download) {
+            self.save_downloads();
+            self.update_download_queue();
+        }
+    }
+
+    /// Remove the download at `index` from the queue, cancelling it if running
+    fn remove_download(&mut self, index: usize) {
+        let Some(download) = self.downloads.get(index).cloned() else {
+            return;
+        };
+        self.downloader.cancel(&download.url);
+        self.downloads.remove(&download.url);
+        self.save_downloads();
+        self.update_download_queue();
+    }
+
+    fn save_downloads(&mut self) {
+        if let Err(err) = self.downloads.save() {
+            self.set_status_message(format!("Could not save downloads: {err}"));
+        }
+    }
+
+    /// mark article as viewed in history.
+    /// If `selected` is true, the article has just been selected, so it's marked as read only if enabled in configuration
+    /// and if it hasn't been explicitly marked as unread
+    fn mark_viewed_article(&mut self, index: usize, selected: bool) {
+        if selected && !self.config.mark_read_on_select() {
+            return;
+        }
         let Some(feed) = self.get_selected_feed().cloned() else {
             return;
         };
-        let Some(article) = feed.articles().nth(index).cloned() else {
+        let Some((source, article)) = self.article_source(&feed, index) else {
             return;
         };
-        let was_read = self.history.is_article_read(feed_name.as_str(), &article);
-        if !was_read {
-            self.history.read(feed_name.as_str(), &article);
-            // update view
-            self.reload_article_list(&feed, Some(index));
+        // viewing an updated article clears its updated flag
+        let was_read = self.history.is_article_read(&source, &article)
+            && !self.history.is_article_updated(&source, &article);
+        if was_read {
+            return;
         }
-        // if the entire source is read, reload source list
-        if self.history.is_source_read(&feed_name) {
-            let state = self
-                .kiosk
-                .get_feed_state(&feed_name)
-                .map(FlatFeedState::from)
-                .unwrap_or(FlatFeedState::Success);
-            self.update_feed_list_item(&feed_name, state, true);
+        if selected {
+            self.history.view(&source, &article);
+        } else {
+            self.history.read(&source, &article);
         }
+        self.sync_duplicates(&source, &article);
+        self.update_read_state(&feed, index);
+    }
+
+    /// Toggle the read state of the article at `index`
+    fn toggle_article_read(&mut self, index: usize) {
+        let Some(feed) = self.get_selected_feed().cloned() else {
+            return;
+        };
+        let Some((source, article)) = self.article_source(&feed, index) else {
+            return;
+        };
+        if self.history.toggle(&source, &article) {
+            self.set_status_message("Marked as read");
+        } else {
+            self.set_status_message("Marked as unread");
+        }
+        self.sync_duplicates(&source, &article);
+        self.update_read_state(&feed, index);
+    }
+
+    /// Apply the read state of the article of `source` to its duplicates in the other sources
+    fn sync_duplicates(&mut self, source: &str, article: &Article) {
+        let read = self.history.is_article_read(source, article);
+        let duplicates: Vec<(String, Article)> = self
+            .duplicates
+            .get(source, &article.id)
+            .into_iter()
+            .filter_map(|(source, id)| {
+                self.kiosk
+                    .get_feed(source)?
+                    .articles()
+                    .find(|x| x.id == id)
+                    .map(|article| (source.to_string(), article.clone()))
+            })
+            .collect();
+        for (source, article) in duplicates {
+            if read != self.history.is_article_read(&source, &article) {
+                self.history.toggle(&source, &article);
+            }
+        }
+    }
+
+    /// Update article list and feed list after the read state of the article at `index` has changed
+    fn update_read_state(&mut self, feed: &Feed, index: usize) {
+        self.unread_count = None;
+        self.reload_article_list(feed, Some(index));
+        self.update_feed_list_items();
+    }
+
+    /// Select the next unread article across all feeds, or the previous one if `forward` is false.
+    /// The selected feed is changed if necessary
+    fn go_to_unread(&mut self, forward: bool) {
+        let sources: Vec<String> = self.sorted_sources().into_iter().cloned().collect();
+        let feeds: Vec<Vec<bool>> = sources
+            .iter()
+            .map(|name| {
+                self.kiosk
+                    .get_feed(name)
+                    .map(|feed| {
+                        feed.articles()
+                            .map(|article| self.history.is_article_read(name, article))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        let current_feed = match self.application.state(&Id::FeedList) {
+            Ok(State::One(StateValue::Usize(index))) => index,
+            _ => 0,
+        };
+        let current_article = self.get_selected_article_index().unwrap_or_default();
+        let Some((feed_index, article_index)) =
+            find_unread(&feeds, (current_feed, current_article), forward)
+        else {
+            self.set_status_message("No unread articles");
+            return;
+        };
+        // select feed
+        if feed_index != current_feed {
+            assert!(
+                self.application
+                    .attr(
+                        &Id::FeedList,
+                        Attribute::Value,
+                        AttrValue::Payload(PropPayload::One(PropValue::Usize(feed_index)))
+                    )
+                    .is_ok()
+            );
+        }
+        let Some(feed) = self.get_selected_feed().cloned() else {
+            return;
+        };
+        // select article
+        self.reload_article_list(&feed, Some(article_index));
+        self.update_article(article_index);
+        self.mark_viewed_article(article_index, true);
     }
 
     /// Mark a source as read
     fn mark_source_as_read(&mut self, name: &str) {
-        self.history.read_source(name);
+        if self.is_merged_feed(name) {
+            self.mark_all_sources_as_read();
+            return;
+        }
+        let count = self.history.read_source(name);
+        self.unread_count = None;
+        if let Some(feed) = self.kiosk.get_feed(name).cloned() {
+            for article in feed.articles() {
+                self.sync_duplicates(name, article);
+            }
+        }
+        self.set_status_message(format!("Marked {count} articles read"));
         let selected_line = self.application.state(&Id::FeedList).ok();
         let selected_line = match selected_line {
             Some(State::One(StateValue::Usize(line))) => Some(line),
@@ -290,18 +1193,32 @@ impl Ui {
             return;
         };
         self.reload_article_list(&feed, selected_line);
+        self.update_feed_list_items();
+    }
 
-        let state = self
-            .kiosk
-            .get_feed_state(name)
-            .map(FlatFeedState::from)
-            .unwrap_or(FlatFeedState::Success);
-        self.update_feed_list_item(&feed.name, state, true);
+    /// Mark a source as unread
+    fn mark_source_as_unread(&mut self, name: &str) {
+        let count = if self.is_merged_feed(name) {
+            let sources: Vec<String> = self.kiosk.sources().into_iter().cloned().collect();
+            sources.iter().map(|x| self.history.unread_source(x)).sum()
+        } else {
+            self.history.unread_source(name)
+        };
+        self.unread_count = None;
+        self.set_status_message(format!("Marked {count} articles unread"));
+        let Some(feed) = self.get_selected_feed().cloned() else {
+            return;
+        };
+        let selected_line = self.get_selected_article_index();
+        self.reload_article_list(&feed, selected_line);
+        self.update_feed_list_items();
     }
 
     /// Mark all sources as read
     fn mark_all_sources_as_read(&mut self) {
-        self.history.read_all();
+        let count = self.history.read_all();
+        self.unread_count = None;
+        self.set_status_message(format!("Marked {count} articles read"));
 
         let selected_line = self.application.state(&Id::FeedList).ok();
         let selected_line = match selected_line {
@@ -312,11 +1229,166 @@ impl Ui {
             return;
         };
         self.reload_article_list(&feed, selected_line);
+        self.update_feed_list_items();
+    }
 
-        let sources_with_states = self.kiosk.get_state();
-        for (feed_name, state) in sources_with_states {
-            self.update_feed_list_item(&feed_name, state, true);
+    /// Validate the source form and start the test fetch of the new or edited source.
+    /// A renamed source is saved immediately
+    fn submit_source_form(&mut self) {
+        let name = self.source_form_value(&Id::SourceFormName);
+        let url = self.source_form_value(&Id::SourceFormUrl);
+        if name.is_empty() {
+            self.set_status_message("Source name can't be empty");
+            return;
+        }
+        if name == MERGED_FEED_NAME {
+            self.set_status_message(format!(r#"The source name "{name}" is reserved"#));
+            return;
+        }
+        let source = match FeedSource::from_str(&url) {
+            Ok(source) => source,
+            Err(err) => {
+                self.set_status_message(err);
+                return;
+            }
+        };
+        let original = self.source_form.clone();
+        if original.as_deref() != Some(name.as_str()) && self.config.sources.contains_key(&name) {
+            self.set_status_message(format!(r#"A source named "{name}" already exists"#));
+            return;
+        }
+        if self.pending_source.is_some() {
+            self.set_status_message("Another source is being checked, please wait");
+            return;
         }
+        self.umount_source_form();
+        match original {
+            None => {
+                let selected = self.get_selected_feed_name();
+                self.kiosk.insert_feed(&name, FeedState::Loading);
+                self.remount_feed_list(selected.as_deref());
+                self.check_source(SourceChange::Add(name, source));
+            }
+            Some(original) => {
+                if original != name && !self.rename_source(&original, &name) {
+                    return;
+                }
+                if self.config.sources.get(&name).map(|x| &x.source) != Some(&source) {
+                    self.check_source(SourceChange::Edit(name, source));
+                }
+            }
+        }
+    }
+
+    /// Start the test fetch of a new or edited source; the change is saved once the feed has been fetched
+    fn check_source(&mut self, change: SourceChange) {
+        let (SourceChange::Add(name, source) | SourceChange::Edit(name, source)) = &change else {
+            return;
+        };
+        let (name, source) = (name.clone(), source.clone());
+        self.pending_source = Some(change);
+        self.fetch_source(&name, source);
+        self.set_status_message(format!(r#"Checking "{name}"…"#));
+    }
+
+    /// Save the pending source change if the test fetch succeeded, otherwise discard it.
+    /// Returns whether the fetched feed must be handled
+    fn complete_pending_source(&mut self, change: SourceChange, err: Option<&FeedError>) -> bool {
+        let name = change.name().to_string();
+        let Some(err) = err else {
+            match self.write_source_change(&change) {
+                Ok(()) => self.set_status_message(format!(r#"Source "{name}" saved"#)),
+                Err(err) => self.mount_error_popup(err),
+            }
+            return true;
+        };
+        self.mount_error_popup(format!(
+            r#"Could not fetch "{name}": {err}. The source has not been saved"#
+        ));
+        if let SourceChange::Add(..) = change {
+            let selected = self.get_selected_feed_name();
+            self.kiosk.remove_feed(&name);
+            self.unread_count = None;
+            self.remount_feed_list(selected.as_deref());
+        } else if let Some(source) = self.config.sources.get(&name).map(|x| x.source.clone()) {
+            // restore the previous feed
+            self.fetch_source(&name, source);
+        }
+        false
+    }
+
+    /// Rename a source; its history and downloads are kept, since they are keyed by the source identity.
+    /// Returns whether the source has been renamed
+    fn rename_source(&mut self, name: &str, new_name: &str) -> bool {
+        let change = SourceChange::Rename(name.to_string(), new_name.to_string());
+        if let Err(err) = self.write_source_change(&change) {
+            self.mount_error_popup(err);
+            return false;
+        }
+        self.kiosk.rename_feed(name, new_name);
+        self.unread_count = None;
+        self.remount_feed_list(Some(new_name));
+        self.update_duplicates();
+        self.set_status_message(format!(r#"Renamed "{name}" to "{new_name}""#));
+        true
+    }
+
+    /// Remove the currently selected source and its downloads. Downloaded files are kept, and so is its history, in case
+    /// it's added back, until it exceeds the retention policy
+    fn remove_selected_source(&mut self) {
+        let Some(name) = self.get_selected_feed_name() else {
+            return;
+        };
+        let identity = self.config.sources.get(&name).map(|x| x.identity());
+        if let Err(err) = self.write_source_change(&SourceChange::Remove(name.clone())) {
+            self.mount_error_popup(err);
+            return;
+        }
+        self.pending_source.take_if(|x| x.name() == name);
+        self.kiosk.remove_feed(&name);
+        self.history.drop_removed_sources();
+        self.unread_count = None;
+        for download in identity
+            .map(|x| self.downloads.remove_source(&x))
+            .unwrap_or_default()
+        {
+            self.downloader.cancel(&download.url);
+        }
+        self.save_downloads();
+        self.update_download_queue();
+        self.remount_feed_list(None);
+        self.update_duplicates();
+        match self.get_selected_feed().cloned() {
+            Some(feed) => {
+                self.reload_article_list(&feed, None);
+                self.update_article(0);
+            }
+            None => self.clear_article_list(),
+        }
+        self.set_status_message(format!(r#"Removed "{name}""#));
+    }
+
+    /// Write a change to the sources into the configuration file, keeping its comments and formatting
+    fn write_source_change(&mut self, change: &SourceChange) -> Result<(), String> {
+        let path = self.config_path.clone().ok_or_else(|| {
+            String::from("Could not find a configuration path on your operating system")
+        })?;
+        let reader = file_helpers::open_file_read(&path)
+            .map_err(|e| format!("Could not read configuration file: {e}"))?;
+        let mut data = Vec::new();
+        config_serializer::serialize(reader, &mut data, change)
+            .map_err(|e| format!("Could not update configuration: {e}"))?;
+        file_helpers::write_file_atomic(&path, &data)
+            .map_err(|e| format!("Could not write configuration file: {e}"))?;
+        self.config.apply_source_change(change);
+        self.history
+            .set_source_identities(self.config.source_identities());
+        self.downloads
+            .set_source_identities(self.config.source_identities());
+        self.unread_count = None;
+        // don't reload the configuration which has just been written
+        self.config_modified = Self::modified_time(&path);
+        Ok(())
     }
 
     fn reload_article_list(&mut self, feed: &Feed, selected_line: Option<usize>) {
@@ -344,26 +1416,77 @@ impl Update<Msg> for Ui {
             }
             Msg::ArticleChanged(article) => {
                 self.update_article(article);
-                self.mark_viewed_article(article);
+                self.mark_viewed_article(article, true);
+                None
+            }
+            Msg::ArticleScrolledToEnd => {
+                if let Some(index) = self.get_selected_article_index() {
+                    self.mark_viewed_article(index, false);
+                }
                 None
             }
             Msg::ArticleListBlur => {
                 assert!(self.application.active(&Id::FeedList).is_ok());
                 None
             }
+            Msg::ClearErrorLog => {
+                self.error_log.clear();
+                self.update_error_log();
+                None
+            }
+            Msg::CloseDownloadQueue => {
+                self.umount_download_queue();
+                None
+            }
             Msg::CloseErrorPopup => {
                 self.umount_error_popup();
                 None
             }
+            Msg::CloseLinkPicker => {
+                self.umount_link_picker();
+                None
+            }
             Msg::CloseQuitPopup => {
                 self.umount_quit_popup();
                 None
             }
+            Msg::CloseRemoveSourcePopup => {
+                self.umount_remove_source_popup();
+                None
+            }
+            Msg::CloseSourceForm => {
+                self.umount_source_form();
+                None
+            }
+            Msg::CopyArticleMarkdownLink => {
+                if let Some(article) = self.get_selected_article() {
+                    let title = article.title.as_deref().unwrap_or(article.url.as_str());
+                    let link = format!("[{}]({})", title, article.url);
+                    self.copy_to_clipboard(&link, "Markdown link");
+                }
+                None
+            }
+            Msg::CopyArticleText => {
+                if let Some(text) = self.get_selected_article().map(|x| x.summary.clone()) {
+                    self.copy_to_clipboard(&text, "Article text");
+                }
+                None
+            }
+            Msg::CopyArticleUrl => {
+                if let Some(url) = self.get_selected_article().map(|x| x.url.clone()) {
+                    self.copy_to_clipboard(&url, "Url");
+                }
+                None
+            }
+            Msg::DownloadEnclosure => {
+                self.download_enclosure();
+                None
+            }
             Msg::FeedChanged(feed) => {
                 let feed = self.sorted_sources().get(feed).cloned()?;
                 let feed = self.kiosk.get_feed(feed.as_str()).cloned()?;
                 // mark first article as read
-                self.mark_viewed_article(0);
+                self.mark_viewed_article(0, true);
                 // Update feed list item
                 self.reload_article_list(&feed, None);
                 // Then load the first article of feed
@@ -377,7 +1500,10 @@ impl Update<Msg> for Ui {
             }
             Msg::FetchSource => {
                 if let Some(name) = self.get_selected_feed_name() {
-                    let uri = self.config.sources.get(&name).cloned();
+                    if self.is_merged_feed(&name) {
+                        self.fetch_all_sources();
+                    }
+                    let uri = self.config.sources.get(&name).map(|x| x.source.clone());
                     if let Some(uri) = uri {
                         self.fetch_source(name.as_str(), uri)
                     }
@@ -392,6 +1518,14 @@ impl Update<Msg> for Ui {
                 let _ = self.application.active(&Id::ArticleSummary);
                 None
             }
+            Msg::GoToNextUnread => {
+                self.go_to_unread(true);
+                None
+            }
+            Msg::GoToPreviousUnread => {
+                self.go_to_unread(false);
+                None
+            }
             Msg::MarkAllSourcesAsRead => {
                 self.mark_all_sources_as_read();
                 None
@@ -402,21 +1536,146 @@ impl Update<Msg> for Ui {
                 }
                 None
             }
+            Msg::MarkSourceAsUnread => {
+                if let Some(name) = self.get_selected_feed_name() {
+                    self.mark_source_as_unread(name.as_str());
+                }
+                None
+            }
             Msg::OpenArticle => {
                 if let Ok(Some(AttrValue::String(url))) =
                     self.application.query(&Id::ArticleLink, Attribute::Text)
                 {
-                    if let Err(err) = open_helpers::open_link(url.as_str()) {
-                        self.mount_error_popup(err);
+                    match self.open_url(url.as_str(), None) {
+                        Ok(()) => self.set_status_message("Link opened"),
+                        Err(err) => self.mount_error_popup(err),
+                    }
+                    if let Some(index) = self.get_selected_article_index() {
+                        self.mark_viewed_article(index, false);
                     }
                 }
                 None
             }
+            Msg::OpenArticleLink(index) => {
+                self.umount_link_picker();
+                self.open_article_link(index);
+                None
+            }
+            Msg::PlayEnclosure => {
+                self.play_enclosure();
+                None
+            }
             Msg::Quit => Some(Msg::Quit),
+            Msg::ReadInPager => {
+                self.read_in_pager();
+                None
+            }
+            Msg::ReloadConfig => {
+                self.config_modified = self.config_path.as_deref().and_then(Self::modified_time);
+                self.reload_config();
+                None
+            }
+            Msg::RemoveDownload(index) => {
+                self.remove_download(index);
+                None
+            }
+            Msg::RemoveSource => {
+                self.umount_remove_source_popup();
+                self.remove_selected_source();
+                None
+            }
+            Msg::RetryDownload(index) => {
+                self.retry_download(index);
+                None
+            }
+            Msg::RetryErrorSource(index) => {
+                self.retry_error_source(index);
+                None
+            }
+            Msg::ShowAddSourceForm => {
+                self.source_form = None;
+                self.mount_source_form("", "");
+                None
+            }
+            Msg::ShowEditSourceForm => {
+                if let Some(name) = self
+                    .get_selected_feed_name()
+                    .filter(|name| !self.is_merged_feed(name))
+                {
+                    let url = self
+                        .config
+                        .sources
+                        .get(&name)
+                        .map(|x| x.source.to_string())
+                        .unwrap_or_default();
+                    self.mount_source_form(&name, &url);
+                    self.source_form = Some(name);
+                }
+                None
+            }
+            Msg::ShowLinkPicker => {
+                let links = self
+                    .get_selected_article()
+                    .map(|article| article.links.clone())
+                    .unwrap_or_default();
+                if links.is_empty() {
+                    self.set_status_message("No links in this article");
+                } else {
+                    self.mount_link_picker(&links);
+                }
+                None
+            }
             Msg::ShowQuitPopup => {
                 self.mount_quit_popup();
                 None
             }
+            Msg::ShowRemoveSourcePopup => {
+                if let Some(name) = self
+                    .get_selected_feed_name()
+                    .filter(|name| !self.is_merged_feed(name))
+                {
+                    self.mount_remove_source_popup(&name);
+                }
+                None
+            }
+            Msg::SourceFormBlur => {
+                self.blur_source_form();
+                None
+            }
+            Msg::SubmitSourceForm => {
+                self.submit_source_form();
+                None
+            }
+            Msg::ToggleDownloadQueue => {
+                if self.application.mounted(&Id::DownloadQueue) {
+                    self.umount_download_queue();
+                } else {
+                    self.mount_download_queue();
+                }
+                None
+            }
+            Msg::ToggleArticleRead => {
+                if let Some(index) = self.get_selected_article_index() {
+                    self.toggle_article_read(index);
+                }
+                None
+            }
+            Msg::ToggleErrorLog => {
+                if self.application.mounted(&Id::ErrorLog) {
+                    self.umount_error_log();
+                } else {
+                    self.mount_error_log();
+                }
+                None
+            }
+            Msg::ToggleStats => {
+                if self.application.mounted(&Id::Stats) {
+                    self.umount_stats();
+                } else {
+                    self.mount_stats();
+                }
+                None
+            }
             Msg::None => None,
         }
     }
@@ -424,6 +1683,12 @@ impl Update<Msg> for Ui {
 
 impl Drop for Ui {
     fn drop(&mut self) {
+        // save and sync history even if the ui terminated abnormally
+        if let Some(sync) = self.history_sync.as_mut() {
+            sync.mark_changed();
+            let _ = sync.sync(&mut self.history);
+        }
+        let _ = self.history.save();
         let _ = self.terminal.restore();
     }
 }
@@ -7,10 +7,17 @@ use super::Msg;
 mod article;
 pub mod lists;
 mod popups;
+mod status;
 
-pub use article::{ArticleAuthors, ArticleDate, ArticleLink, ArticleSummary, ArticleTitle};
+pub use article::{
+    ArticleAuthors, ArticleDate, ArticleEnclosure, ArticleLink, ArticleSummary, ArticleTitle,
+};
 pub use lists::{ArticleList, FeedList};
-pub use popups::{ErrorPopup, QuitPopup};
+pub use popups::{
+    DownloadQueuePopup, ErrorLogPanel, ErrorPopup, LinkPickerPopup, QuitPopup, RemoveSourcePopup,
+    SourceFormInput, StatsPopup,
+};
+pub use status::StatusBar;
 use tui_realm_stdlib::Phantom;
 use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::{Component, Event, MockComponent, NoUserEvent};
@@ -24,6 +31,26 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
     fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
         match ev {
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::ShowQuitPopup),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('d'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => Some(Msg::ToggleDownloadQueue),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('e'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => Some(Msg::ToggleErrorLog),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('l'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => Some(Msg::ReloadConfig),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('n'),
+                ..
+            }) => Some(Msg::GoToNextUnread),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('N'),
+                ..
+            }) => Some(Msg::GoToPreviousUnread),
             Event::Keyboard(KeyEvent {
                 code: Key::Char('r'),
                 modifiers: KeyModifiers::CONTROL,
@@ -32,10 +59,18 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
                 code: Key::Char('r'),
                 ..
             }) => Some(Msg::FetchSource),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('s'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => Some(Msg::ToggleStats),
             Event::Keyboard(KeyEvent {
                 code: Key::Char('v'),
                 modifiers: KeyModifiers::CONTROL,
             }) => Some(Msg::MarkAllSourcesAsRead),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('V'),
+                ..
+            }) => Some(Msg::MarkSourceAsUnread),
             Event::Keyboard(KeyEvent {
                 code: Key::Char('v'),
                 ..
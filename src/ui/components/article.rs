@@ -2,17 +2,17 @@
 //!
 //! Components related to the article area
 
-use chrono::{DateTime, Local};
 use tui_realm_stdlib::{Label, Paragraph};
-use tui_realm_textarea::TextArea;
-use tuirealm::command::{Cmd, Direction, Position};
-use tuirealm::event::{Key, KeyEvent};
+use tui_realm_textarea::{TEXTAREA_CMD_MOVE_BOTTOM, TEXTAREA_CMD_MOVE_TOP, TextArea};
+use tuirealm::command::{Cmd, Direction};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::props::{
     Alignment, BorderSides, BorderType, Borders, Color, Style, TextModifiers, TextSpan,
 };
 use tuirealm::{Component, Event, MockComponent, NoUserEvent};
 
 use super::Msg;
+use crate::feed::Enclosure;
 use crate::helpers::{fmt as fmt_helpers, strings as str_helpers};
 
 #[derive(MockComponent)]
@@ -44,16 +44,12 @@ pub struct ArticleDate {
 }
 
 impl ArticleDate {
-    pub fn new(datetime: Option<DateTime<Local>>) -> Self {
+    pub fn new(date: Option<&str>) -> Self {
         Self {
             component: Label::default()
                 .foreground(Color::LightGreen)
                 .modifiers(TextModifiers::BOLD | TextModifiers::ITALIC)
-                .text(
-                    datetime
-                        .map(|x| fmt_helpers::format_datetime(x, "%A %d %B %Y, %H:%M"))
-                        .unwrap_or_default(),
-                ),
+                .text(date.unwrap_or_default()),
         }
     }
 }
@@ -105,8 +101,64 @@ impl Component<Msg, NoUserEvent> for ArticleLink {
     }
 }
 
+#[derive(MockComponent)]
+pub struct ArticleEnclosure {
+    component: Label,
+}
+
+impl ArticleEnclosure {
+    pub fn new(enclosures: &[Enclosure]) -> Self {
+        Self {
+            component: Label::default()
+                .foreground(Color::LightMagenta)
+                .modifiers(TextModifiers::BOLD)
+                .text(Self::fmt_enclosures(enclosures)),
+        }
+    }
+
+    /// Describe the first enclosure (e.g. `♫ audio/mpeg · 23.8 MiB · 1:02:03 (+1)`)
+    fn fmt_enclosures(enclosures: &[Enclosure]) -> String {
+        let Some(enclosure) = enclosures.first() else {
+            return String::new();
+        };
+        let mut parts = vec![
+            enclosure
+                .mime
+                .clone()
+                .unwrap_or_else(|| String::from("media")),
+        ];
+        if let Some(size) = enclosure.size.filter(|x| *x > 0) {
+            parts.push(fmt_helpers::format_bytes(size));
+        }
+        if let Some(duration) = enclosure.duration {
+            parts.push(fmt_helpers::format_duration(duration));
+        }
+        let mut text = format!("♫ {}", parts.join(" · "));
+        if enclosures.len() > 1 {
+            text.push_str(&format!(" (+{})", enclosures.len() - 1));
+        }
+        text
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ArticleEnclosure {
+    fn on(&mut self, _: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
+
+/// Amount of rows scrolled by [`Cmd::Scroll`]
+const SUMMARY_SCROLL_STEP: usize = 4;
+
 pub struct ArticleSummary<'a> {
     component: TextArea<'a>,
+    /// Amount of rows in the summary
+    rows: usize,
+    /// Row of the cursor. The textarea doesn't expose its cursor, so it is kept in sync here with the same rules:
+    /// the cursor moves by whole rows and stops at the first and at the last row
+    cursor: usize,
+    /// Whether the end of the summary has been reached
+    reached_end: bool,
 }
 
 impl MockComponent for ArticleSummary<'_> {
@@ -132,9 +184,14 @@ impl MockComponent for ArticleSummary<'_> {
 }
 
 impl ArticleSummary<'_> {
-    pub fn new(summary: &str, width: usize) -> Self {
+    pub fn new(summary: &str, links: &[String], width: usize) -> Self {
+        let mut rows = Self::make_summary_rows(summary, width);
+        rows.extend(Self::make_links_rows(links, width));
         Self {
-            component: TextArea::new(Self::make_summary_rows(summary, width))
+            rows: rows.len(),
+            cursor: 0,
+            reached_end: false,
+            component: TextArea::new(rows)
                 .borders(
                     Borders::default()
                         .color(Color::LightCyan)
@@ -142,7 +199,54 @@ impl ArticleSummary<'_> {
                 )
                 .title("Summary", Alignment::Left)
                 .cursor_style(Style::default())
-                .scroll_step(4),
+                .scroll_step(SUMMARY_SCROLL_STEP),
+        }
+    }
+
+    /// Scroll down by `steps` and report whether the end of the summary has been reached for the first time
+    fn scroll_down(&mut self, steps: usize) -> Option<Msg> {
+        for _ in 0..steps {
+            self.perform(Cmd::Scroll(Direction::Down));
+        }
+        self.cursor = (self.cursor + steps * SUMMARY_SCROLL_STEP).min(self.last_row());
+        self.check_end()
+    }
+
+    /// Scroll up by `steps`
+    fn scroll_up(&mut self, steps: usize) -> Option<Msg> {
+        for _ in 0..steps {
+            self.perform(Cmd::Scroll(Direction::Up));
+        }
+        self.cursor = self.cursor.saturating_sub(steps * SUMMARY_SCROLL_STEP);
+        Some(Msg::None)
+    }
+
+    /// Go to the first row of the summary
+    fn go_to_top(&mut self) -> Option<Msg> {
+        self.perform(Cmd::Custom(TEXTAREA_CMD_MOVE_TOP));
+        self.cursor = 0;
+        Some(Msg::None)
+    }
+
+    /// Go to the last row of the summary and report whether the end of the summary has been reached for the first time
+    fn go_to_bottom(&mut self) -> Option<Msg> {
+        self.perform(Cmd::Custom(TEXTAREA_CMD_MOVE_BOTTOM));
+        self.cursor = self.last_row();
+        self.check_end()
+    }
+
+    /// Get the index of the last row of the summary
+    fn last_row(&self) -> usize {
+        self.rows.saturating_sub(1)
+    }
+
+    /// Report [`Msg::ArticleScrolledToEnd`] if the cursor has reached the last row for the first time
+    fn check_end(&mut self) -> Option<Msg> {
+        if !self.reached_end && self.cursor >= self.last_row() {
+            self.reached_end = true;
+            Some(Msg::ArticleScrolledToEnd)
+        } else {
+            Some(Msg::None)
         }
     }
 
@@ -178,6 +282,21 @@ impl ArticleSummary<'_> {
         }
         lines
     }
+
+    /// Make rows for the link list at the bottom of the summary
+    fn make_links_rows(links: &[String], width: usize) -> Vec<String> {
+        if links.is_empty() {
+            return Vec::new();
+        }
+        let mut rows = vec![String::new(), String::from("Links:")];
+        for (i, link) in links.iter().enumerate() {
+            rows.extend(Self::make_summary_rows(
+                &format!("[{}] {}", i + 1, link),
+                width,
+            ));
+        }
+        rows
+    }
 }
 
 impl Component<Msg, NoUserEvent> for ArticleSummary<'_> {
@@ -185,47 +304,67 @@ impl Component<Msg, NoUserEvent> for ArticleSummary<'_> {
         match ev {
             Event::Keyboard(KeyEvent {
                 code: Key::Down, ..
-            }) => {
-                self.perform(Cmd::Scroll(Direction::Down));
-                Some(Msg::None)
-            }
-            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
-                self.perform(Cmd::Scroll(Direction::Up));
-                Some(Msg::None)
-            }
+            }) => self.scroll_down(1),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => self.scroll_up(1),
             Event::Keyboard(KeyEvent {
                 code: Key::PageDown,
                 ..
             }) => {
                 // Scroll twice
-                self.perform(Cmd::Scroll(Direction::Down));
-                self.perform(Cmd::Scroll(Direction::Down));
-                Some(Msg::None)
+                self.scroll_down(2)
             }
             Event::Keyboard(KeyEvent {
                 code: Key::PageUp, ..
             }) => {
                 // Scroll twice
-                self.perform(Cmd::Scroll(Direction::Up));
-                self.perform(Cmd::Scroll(Direction::Up));
-                Some(Msg::None)
+                self.scroll_up(2)
             }
             Event::Keyboard(KeyEvent {
                 code: Key::Home, ..
-            }) => {
-                self.perform(Cmd::GoTo(Position::Begin));
-                Some(Msg::None)
-            }
-            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
-                self.perform(Cmd::GoTo(Position::End));
-                Some(Msg::None)
-            }
+            }) => self.go_to_top(),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => self.go_to_bottom(),
             Event::Keyboard(KeyEvent {
                 code: Key::Left, ..
             }) => Some(Msg::ArticleBlur),
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => Some(Msg::OpenArticle),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('l'),
+                ..
+            }) => Some(Msg::ShowLinkPicker),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('m'),
+                ..
+            }) => Some(Msg::ToggleArticleRead),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('o'),
+                ..
+            }) => Some(Msg::ReadInPager),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('y'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => Some(Msg::CopyArticleText),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('y'),
+                ..
+            }) => Some(Msg::CopyArticleUrl),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('Y'),
+                ..
+            }) => Some(Msg::CopyArticleMarkdownLink),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                ..
+            }) => Some(Msg::PlayEnclosure),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('d'),
+                modifiers: KeyModifiers::NONE,
+            }) => Some(Msg::DownloadEnclosure),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch @ '1'..='9'),
+                ..
+            }) => Some(Msg::OpenArticleLink(ch as usize - '1' as usize)),
             _ => None,
         }
     }
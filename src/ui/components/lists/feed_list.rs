@@ -72,7 +72,7 @@ impl FeedList {
         }
     }
 
-    fn feed_state_to_span(state: &FlatFeedState, loading_step: char) -> Span {
+    fn feed_state_to_span(state: &FlatFeedState, loading_step: char) -> Span<'_> {
         match *state {
             FlatFeedState::Success => Span::from("  "),
             FlatFeedState::Loading => Span::from(format!("{} ", loading_step)),
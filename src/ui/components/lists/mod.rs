@@ -9,7 +9,7 @@ pub use feed_list::{
 };
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
-use tuirealm::event::{Key, KeyEvent};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
 use tuirealm::props::{Alignment, BorderType, Borders, Color, TextSpan};
 use tuirealm::{Component, Event, MockComponent, NoUserEvent, State, StateValue};
 
@@ -55,6 +55,17 @@ impl Component<Msg, NoUserEvent> for FeedList {
                 code: Key::Tab | Key::Right,
                 ..
             }) => return Some(Msg::FeedListBlur),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('a'),
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::ShowAddSourceForm),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('e'),
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::ShowEditSourceForm),
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => return Some(Msg::ShowRemoveSourcePopup),
             _ => return None,
         };
         if let CmdResult::Changed(State::One(StateValue::Usize(index))) = cmd_result {
@@ -135,6 +146,38 @@ impl Component<Msg, NoUserEvent> for ArticleList {
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => return Some(Msg::OpenArticle),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('l'),
+                ..
+            }) => return Some(Msg::ShowLinkPicker),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('m'),
+                ..
+            }) => return Some(Msg::ToggleArticleRead),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('o'),
+                ..
+            }) => return Some(Msg::ReadInPager),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('y'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => return Some(Msg::CopyArticleText),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('y'),
+                ..
+            }) => return Some(Msg::CopyArticleUrl),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('Y'),
+                ..
+            }) => return Some(Msg::CopyArticleMarkdownLink),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                ..
+            }) => return Some(Msg::PlayEnclosure),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('d'),
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::DownloadEnclosure),
             _ => return None,
         };
         if let CmdResult::Changed(State::One(StateValue::Usize(index))) = cmd_result {
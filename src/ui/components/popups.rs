@@ -2,10 +2,12 @@
 //!
 //! Popups components
 
-use tui_realm_stdlib::{Paragraph, Radio};
-use tuirealm::command::{Cmd, CmdResult, Direction};
-use tuirealm::event::{Key, KeyEvent};
-use tuirealm::props::{Alignment, BorderType, Borders, Color, TextModifiers, TextSpan};
+use tui_realm_stdlib::{Input, List, Paragraph, Radio};
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+use tuirealm::props::{
+    Alignment, BorderType, Borders, Color, InputType, Style, TextModifiers, TextSpan,
+};
 use tuirealm::{Component, Event, MockComponent, NoUserEvent, State, StateValue};
 
 use super::Msg;
@@ -96,3 +98,412 @@ impl Component<Msg, NoUserEvent> for ErrorPopup {
         }
     }
 }
+
+#[derive(MockComponent)]
+pub struct LinkPickerPopup {
+    component: List,
+}
+
+impl LinkPickerPopup {
+    pub fn new(links: &[String]) -> Self {
+        Self {
+            component: List::default()
+                .borders(
+                    Borders::default()
+                        .color(Color::LightCyan)
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(Color::LightCyan)
+                .highlighted_color(Color::LightCyan)
+                .highlighted_str("➤ ")
+                .rewind(true)
+                .scroll(true)
+                .step(4)
+                .title("Open link", Alignment::Center)
+                .rows(
+                    links
+                        .iter()
+                        .enumerate()
+                        .map(|(i, link)| vec![TextSpan::from(format!("[{}] {}", i + 1, link))])
+                        .collect(),
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for LinkPickerPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => {
+                self.perform(Cmd::GoTo(Position::Begin));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match self.state() {
+                State::One(StateValue::Usize(index)) => Some(Msg::OpenArticleLink(index)),
+                _ => Some(Msg::CloseLinkPicker),
+            },
+            Event::Keyboard(KeyEvent {
+                code: Key::Esc | Key::Char('l'),
+                ..
+            }) => Some(Msg::CloseLinkPicker),
+            _ => None,
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct DownloadQueuePopup {
+    component: List,
+}
+
+impl DownloadQueuePopup {
+    pub fn new(rows: Vec<Vec<TextSpan>>) -> Self {
+        Self {
+            component: List::default()
+                .borders(
+                    Borders::default()
+                        .color(Color::LightMagenta)
+                        .modifiers(BorderType::Rounded),
+                )
+                .highlighted_color(Color::LightMagenta)
+                .highlighted_str("➤ ")
+                .rewind(true)
+                .scroll(true)
+                .step(4)
+                .title("Downloads (<ENTER> retry, <DEL> remove)", Alignment::Center)
+                .rows(rows),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for DownloadQueuePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match self.state() {
+                State::One(StateValue::Usize(index)) => Some(Msg::RetryDownload(index)),
+                _ => Some(Msg::None),
+            },
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete | Key::Backspace,
+                ..
+            }) => match self.state() {
+                State::One(StateValue::Usize(index)) => Some(Msg::RemoveDownload(index)),
+                _ => Some(Msg::None),
+            },
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::CloseDownloadQueue),
+            _ => None,
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ErrorLogPanel {
+    component: List,
+}
+
+impl ErrorLogPanel {
+    pub fn new(rows: Vec<Vec<TextSpan>>) -> Self {
+        Self {
+            component: List::default()
+                .borders(
+                    Borders::default()
+                        .color(Color::Red)
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(Color::Red)
+                .highlighted_color(Color::Red)
+                .highlighted_str("➤ ")
+                .rewind(true)
+                .scroll(true)
+                .step(4)
+                .title(
+                    "Errors (<ENTER> retry source, <DEL> clear log)",
+                    Alignment::Left,
+                )
+                .rows(rows),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ErrorLogPanel {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match self.state() {
+                State::One(StateValue::Usize(index)) => Some(Msg::RetryErrorSource(index)),
+                _ => Some(Msg::None),
+            },
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete | Key::Backspace,
+                ..
+            }) => Some(Msg::ClearErrorLog),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::ToggleErrorLog),
+            _ => None,
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct StatsPopup {
+    component: List,
+}
+
+impl StatsPopup {
+    pub fn new(rows: Vec<Vec<TextSpan>>) -> Self {
+        Self {
+            component: List::default()
+                .borders(
+                    Borders::default()
+                        .color(Color::LightBlue)
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(Color::LightBlue)
+                .rewind(false)
+                .scroll(true)
+                .step(4)
+                .title("Reading statistics", Alignment::Center)
+                .rows(rows),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for StatsPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Down));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => {
+                self.perform(Cmd::Scroll(Direction::Up));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::ToggleStats),
+            _ => None,
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct SourceFormInput {
+    component: Input,
+}
+
+impl SourceFormInput {
+    pub fn new(title: &str, value: &str, placeholder: &str) -> Self {
+        Self {
+            component: Input::default()
+                .borders(
+                    Borders::default()
+                        .color(Color::LightGreen)
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(Color::LightGreen)
+                .input_type(InputType::Text)
+                .placeholder(placeholder, Style::default().fg(Color::Rgb(128, 128, 128)))
+                .title(title, Alignment::Left)
+                .value(value),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for SourceFormInput {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Left));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Right));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => {
+                self.perform(Cmd::GoTo(Position::Begin));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => {
+                self.perform(Cmd::Delete);
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => {
+                self.perform(Cmd::Cancel);
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+            }) => {
+                self.perform(Cmd::Type(ch));
+                Some(Msg::None)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab | Key::BackTab | Key::Up | Key::Down,
+                ..
+            }) => Some(Msg::SourceFormBlur),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => Some(Msg::SubmitSourceForm),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::CloseSourceForm),
+            _ => None,
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct RemoveSourcePopup {
+    component: Radio,
+}
+
+impl RemoveSourcePopup {
+    pub fn new(name: &str) -> Self {
+        Self {
+            component: Radio::default()
+                .foreground(Color::Red)
+                .borders(
+                    Borders::default()
+                        .color(Color::Red)
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(format!("Remove \"{name}\"?"), Alignment::Center)
+                .rewind(true)
+                .choices(&["Yes", "No"])
+                .value(1),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RemoveSourcePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::CloseRemoveSourcePopup);
+            }
+            _ => return None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::Usize(0))) => Some(Msg::RemoveSource),
+            CmdResult::Submit(_) => Some(Msg::CloseRemoveSourcePopup),
+            _ => Some(Msg::None),
+        }
+    }
+}
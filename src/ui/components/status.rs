@@ -0,0 +1,28 @@
+//! # Status
+//!
+//! Status bar component
+
+use tui_realm_stdlib::Span;
+use tuirealm::props::TextSpan;
+use tuirealm::{Component, Event, MockComponent, NoUserEvent};
+
+use super::Msg;
+
+#[derive(MockComponent)]
+pub struct StatusBar {
+    component: Span,
+}
+
+impl StatusBar {
+    pub fn new(spans: &[TextSpan]) -> Self {
+        Self {
+            component: Span::default().spans(spans),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for StatusBar {
+    fn on(&mut self, _: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
@@ -3,9 +3,21 @@
 //! ui lib
 
 mod client;
+mod downloader;
+pub mod downloads;
+mod duplicates;
+mod error_log;
 mod history;
 mod kiosk;
+mod navigation;
+mod status;
 
 pub use self::client::FeedClient;
-pub use self::history::History;
+pub use self::downloader::Downloader;
+pub use self::downloads::{Download, DownloadStatus, Downloads};
+pub use self::duplicates::{Duplicates, MERGED_FEED_NAME};
+pub use self::error_log::ErrorLog;
+pub use self::history::{History, HistorySync, Retention};
 pub use self::kiosk::{FeedState, FlatFeedState, Kiosk};
+pub use self::navigation::find_unread;
+pub use self::status::StatusMessage;
@@ -19,8 +19,8 @@ impl FeedClient {
         self.workers.push(WorkerThread::start(name, source));
     }
 
-    /// Poll worker threads
-    pub fn poll(&mut self) -> Option<(String, FeedResult<Feed>)> {
+    /// Poll worker threads; returns the name and the source of the first fetched feed, with the result
+    pub fn poll(&mut self) -> Option<(String, FeedSource, FeedResult<Feed>)> {
         let mut i = 0;
         while i < self.workers.len() {
             // if worker at `i` is joinable, join and return
@@ -56,7 +56,10 @@ impl Drop for FeedClient {
 
 /// Thread holder for worker
 #[derive(Debug)]
-struct WorkerThread(Arc<RwLock<bool>>, JoinHandle<(String, FeedResult<Feed>)>);
+struct WorkerThread(
+    Arc<RwLock<bool>>,
+    JoinHandle<(String, FeedSource, FeedResult<Feed>)>,
+);
 
 impl WorkerThread {
     /// Start a new worker thread
@@ -79,7 +82,7 @@ impl WorkerThread {
 
     /// Join thread and consume worker.
     /// Returns thread product
-    pub fn join(self) -> (String, FeedResult<Feed>) {
+    pub fn join(self) -> (String, FeedSource, FeedResult<Feed>) {
         self.1.join().ok().unwrap()
     }
 }
@@ -103,11 +106,15 @@ impl Worker {
     }
 
     /// Run function for worker
-    pub fn run(&mut self) -> (String, FeedResult<Feed>) {
+    pub fn run(&mut self) -> (String, FeedSource, FeedResult<Feed>) {
         // Set running to false
         self.stop();
         // Return to handle
-        (self.name.clone(), Client.fetch(&self.name, &self.source))
+        (
+            self.name.clone(),
+            self.source.clone(),
+            Client.fetch(&self.name, &self.source),
+        )
     }
 
     fn stop(&mut self) {
@@ -137,14 +144,13 @@ mod test {
             "Le Figaro",
             &("https://www.lefigaro.fr/rss/figaro_actualites.xml"
                 .to_string()
-                .try_into()
-                .unwrap()),
+                .into()),
         );
         assert_eq!(client.running(), true);
         // Wait up to 10 seconds before failing
         let start = Instant::now();
         while start.elapsed() < Duration::from_secs(10) {
-            if let Some((name, result)) = client.poll() {
+            if let Some((name, _, result)) = client.poll() {
                 assert_eq!(name.as_str(), "Le Figaro");
                 assert!(result.is_ok());
                 assert_eq!(client.running(), false);
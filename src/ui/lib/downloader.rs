@@ -0,0 +1,335 @@
+//! # Downloader
+//!
+//! Async enclosures downloader. Downloads run on their own workers, separated from the feed client
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// Size of the buffer used to read the response body
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Download progress for a running download
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Downloaded bytes
+    pub downloaded: u64,
+    /// Total size in bytes, if known
+    pub total: Option<u64>,
+    /// Download speed in bytes per second
+    pub speed: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Downloader {
+    workers: Vec<DownloadWorker>,
+}
+
+impl Downloader {
+    /// Start downloading `url` to `path`.
+    /// If a partial download exists for `path`, the download is resumed
+    pub fn download(&mut self, url: &str, path: &Path) {
+        self.workers.push(DownloadWorker::start(url, path));
+    }
+
+    /// Poll workers; returns the url and the result of the first terminated download
+    pub fn poll(&mut self) -> Option<(String, Result<(), String>)> {
+        let index = self.workers.iter().position(|x| x.is_joinable())?;
+        let worker = self.workers.remove(index);
+        Some(worker.join())
+    }
+
+    /// Cancel download for `url`. The partial file is kept, so it can be resumed later
+    pub fn cancel(&mut self, url: &str) {
+        if let Some(worker) = self.workers.iter().find(|x| x.url == url) {
+            worker.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the progress for the download of `url`
+    pub fn progress(&self, url: &str) -> Option<Progress> {
+        self.workers
+            .iter()
+            .find(|x| x.url == url)
+            .and_then(|x| x.progress.read().ok().map(|x| *x))
+    }
+
+    /// Returns the amount of running downloads
+    pub fn running(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for Downloader {
+    fn drop(&mut self) {
+        // cancel all downloads without waiting for workers, which may be blocked on the network;
+        // partial files are kept, so downloads are resumed on the next start
+        for worker in self.workers.iter() {
+            worker.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// -- worker thread
+
+/// Thread holder for a download worker
+#[derive(Debug)]
+struct DownloadWorker {
+    url: String,
+    cancel: Arc<AtomicBool>,
+    progress: Arc<RwLock<Progress>>,
+    thread: JoinHandle<Result<(), String>>,
+}
+
+impl DownloadWorker {
+    /// Start a new download worker
+    fn start(url: &str, path: &Path) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(RwLock::new(Progress::default()));
+        let worker = Worker {
+            url: url.to_string(),
+            path: path.to_path_buf(),
+            cancel: Arc::clone(&cancel),
+            progress: Arc::clone(&progress),
+        };
+        Self {
+            url: url.to_string(),
+            cancel,
+            progress,
+            thread: thread::spawn(move || worker.run()),
+        }
+    }
+
+    /// Returns whether thread is joinable
+    fn is_joinable(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Join thread and consume worker.
+    fn join(self) -> (String, Result<(), String>) {
+        let result = self
+            .thread
+            .join()
+            .unwrap_or_else(|_| Err(String::from("download worker panicked")));
+        (self.url, result)
+    }
+}
+
+// -- worker
+
+/// Worker which downloads an enclosure
+struct Worker {
+    url: String,
+    path: PathBuf,
+    cancel: Arc<AtomicBool>,
+    progress: Arc<RwLock<Progress>>,
+}
+
+impl Worker {
+    /// Run function for worker
+    fn run(self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let part = part_path(&self.path);
+        let offset = std::fs::metadata(&part).map(|x| x.len()).unwrap_or(0);
+        let mut request = ureq::get(&self.url)
+            .config()
+            .http_status_as_error(false)
+            .build();
+        if offset > 0 {
+            request = request.header("Range", &format!("bytes={offset}-"));
+        }
+        let response = request.call().map_err(|e| e.to_string())?;
+        let status = response.status().as_u16();
+        let (mut file, mut downloaded) = match status {
+            // partial file is already complete
+            416 if offset > 0 => return self.complete(&part),
+            206 if offset > 0 => (
+                OpenOptions::new()
+                    .append(true)
+                    .open(&part)
+                    .map_err(|e| e.to_string())?,
+                offset,
+            ),
+            200..=299 => (File::create(&part).map_err(|e| e.to_string())?, 0),
+            status => return Err(format!("HTTP status: {status}")),
+        };
+        let total = response
+            .headers()
+            .get("content-length")
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse::<u64>().ok())
+            .map(|x| x + downloaded);
+        self.set_progress(downloaded, total, 0.0);
+
+        let started = Instant::now();
+        let resumed_from = downloaded;
+        let mut reader = response.into_body().into_reader();
+        let mut buffer = vec![0; BUFFER_SIZE];
+        loop {
+            if self.cancel.load(Ordering::Relaxed) {
+                return Err(String::from("download cancelled"));
+            }
+            let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+            downloaded += read as u64;
+            let elapsed = started.elapsed().as_secs_f64();
+            let speed = if elapsed > 0.0 {
+                (downloaded - resumed_from) as f64 / elapsed
+            } else {
+                0.0
+            };
+            self.set_progress(downloaded, total, speed);
+        }
+        file.flush().map_err(|e| e.to_string())?;
+        drop(file);
+
+        self.complete(&part)
+    }
+
+    /// Move the partial file to its destination
+    fn complete(&self, part: &Path) -> Result<(), String> {
+        std::fs::rename(part, &self.path).map_err(|e| e.to_string())
+    }
+
+    fn set_progress(&self, downloaded: u64, total: Option<u64>, speed: f64) {
+        if let Ok(mut progress) = self.progress.write() {
+            *progress = Progress {
+                downloaded,
+                total,
+                speed,
+            };
+        }
+    }
+}
+
+/// Path of the partial download for `path`
+fn part_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const BODY: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn should_download_file() {
+        let url = serve(1);
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("feed").join("episode.mp3");
+        let mut downloader = Downloader::default();
+        downloader.download(&url, &path);
+        assert_eq!(downloader.running(), 1);
+
+        let (result_url, result) = wait(&mut downloader);
+        assert_eq!(result_url, url);
+        assert!(result.is_ok());
+        assert_eq!(downloader.running(), 0);
+        assert_eq!(std::fs::read(&path).unwrap(), BODY);
+        assert!(!part_path(&path).exists());
+    }
+
+    #[test]
+    fn should_resume_download() {
+        let url = serve(1);
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("episode.mp3");
+        std::fs::write(part_path(&path), &BODY[..10]).unwrap();
+        let mut downloader = Downloader::default();
+        downloader.download(&url, &path);
+
+        let (_, result) = wait(&mut downloader);
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read(&path).unwrap(), BODY);
+    }
+
+    #[test]
+    fn should_fail_download() {
+        let url = format!("{}/not-found", serve(1));
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("episode.mp3");
+        let mut downloader = Downloader::default();
+        downloader.download(&url, &path);
+
+        let (_, result) = wait(&mut downloader);
+        assert_eq!(result, Err(String::from("HTTP status: 404")));
+        assert!(!path.exists());
+    }
+
+    fn wait(downloader: &mut Downloader) -> (String, Result<(), String>) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(10) {
+            if let Some(result) = downloader.poll() {
+                return result;
+            }
+            sleep(Duration::from_millis(50));
+        }
+        panic!("download didn't terminate")
+    }
+
+    /// Serve [`BODY`] on a local HTTP server for `requests` requests; supports `Range` requests.
+    /// Any path but `/` returns 404
+    fn serve(requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut offset = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(range) = line.to_lowercase().strip_prefix("range: bytes=") {
+                        offset = range.trim().trim_end_matches('-').parse().unwrap();
+                    }
+                }
+                let response = if request_line.split_whitespace().nth(1) != Some("/") {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()
+                } else {
+                    let status = if offset > 0 {
+                        "206 Partial Content"
+                    } else {
+                        "200 OK"
+                    };
+                    let body = &BODY[offset..];
+                    let mut response = format!(
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(body);
+                    response
+                };
+                stream.write_all(&response).unwrap();
+            }
+        });
+        url
+    }
+}
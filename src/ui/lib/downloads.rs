@@ -0,0 +1,486 @@
+//! # Downloads
+//!
+//! Registry of the enclosures downloads, stored next to the history
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::history::{History, HistoryResult};
+use crate::config::DownloadsConfig;
+use crate::feed::Article;
+use crate::helpers::{file as file_helpers, open as open_helpers};
+
+const DEFAULT_TEMPLATE: &str = "{feed}/{date} - {title}";
+const DEFAULT_WORKERS: usize = 2;
+
+/// Registry of enclosures downloads.
+///
+/// Queued and running downloads are kept across restarts, so they can be resumed
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Downloads {
+    #[serde(skip)]
+    path: PathBuf,
+    items: Vec<Download>,
+    /// Warning raised while loading the downloads
+    #[serde(skip)]
+    warning: Option<String>,
+    /// Identity of each source, by source name
+    #[serde(skip)]
+    identities: HashMap<String, String>,
+}
+
+/// A single enclosure download
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Download {
+    /// Identity of the source the article belongs to, so that downloads are kept when the source is renamed.
+    /// Downloads queued before sources had an identity are keyed by name
+    pub source: String,
+    pub article_id: String,
+    pub title: String,
+    /// Enclosure url
+    pub url: String,
+    /// Destination path
+    pub path: PathBuf,
+    pub status: DownloadStatus,
+}
+
+/// Describes the state of a download
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DownloadStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed(String),
+}
+
+impl Downloads {
+    /// Load [`Downloads`] from disk.
+    /// Downloads which were running are queued again, in order to be resumed.
+    /// If the file is corrupt, it is moved aside and the downloads are empty; the reason is reported by [`Downloads::take_warning`]
+    pub fn load(path: &Path) -> HistoryResult<Self> {
+        if !path.exists() {
+            return Ok(Downloads {
+                path: path.to_path_buf(),
+                ..Default::default()
+            });
+        }
+
+        let mut reader = File::open(path)?;
+        let mut downloads: Downloads = match serde_json::from_reader(&mut reader) {
+            Ok(downloads) => downloads,
+            Err(err) => {
+                let mut corrupt_name = path.file_name().unwrap_or_default().to_os_string();
+                corrupt_name.push(".corrupt");
+                let corrupt_path = path.with_file_name(corrupt_name);
+                std::fs::rename(path, &corrupt_path)?;
+                Downloads {
+                    warning: Some(format!(
+                        "Downloads were corrupt ({err}) and have been reset. The corrupt file has been kept at {}",
+                        corrupt_path.display()
+                    )),
+                    ..Default::default()
+                }
+            }
+        };
+        downloads.path = path.to_path_buf();
+        for download in downloads.items.iter_mut() {
+            if download.status == DownloadStatus::Running {
+                download.status = DownloadStatus::Queued;
+            }
+        }
+
+        Ok(downloads)
+    }
+
+    /// Take the warning raised while loading the downloads, if any
+    pub fn take_warning(&mut self) -> Option<String> {
+        self.warning.take()
+    }
+
+    /// Save [`Downloads`] to disk
+    pub fn save(&self) -> HistoryResult<()> {
+        file_helpers::write_file_atomic(&self.path, serde_json::to_vec(self)?)?;
+
+        Ok(())
+    }
+
+    /// Init path for downloads file of `profile`, which is stored next to the history file
+    pub fn default_path(profile: Option<&str>) -> HistoryResult<PathBuf> {
+        History::default_path(profile).map(|path| path.with_file_name("downloads.json"))
+    }
+
+    /// Enqueue a download.
+    /// Returns false if the enclosure is already queued, running or downloaded.
+    /// Failed downloads are queued again
+    pub fn enqueue(&mut self, download: Download) -> bool {
+        match self.items.iter_mut().find(|x| x.url == download.url) {
+            Some(item) if matches!(item.status, DownloadStatus::Failed(_)) => {
+                item.status = DownloadStatus::Queued;
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.items.push(download);
+                true
+            }
+        }
+    }
+
+    /// Remove the download associated to `url`
+    pub fn remove(&mut self, url: &str) -> Option<Download> {
+        let index = self.items.iter().position(|x| x.url == url)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Get the next queued download
+    pub fn next_queued(&self) -> Option<&Download> {
+        self.items
+            .iter()
+            .find(|x| x.status == DownloadStatus::Queued)
+    }
+
+    /// Set status for download associated to `url`
+    pub fn set_status(&mut self, url: &str, status: DownloadStatus) {
+        if let Some(download) = self.items.iter_mut().find(|x| x.url == url) {
+            download.status = status;
+        }
+    }
+
+    /// Returns whether an enclosure of the article of the source named `source` has been downloaded
+    pub fn is_downloaded(&self, source: &str, article_id: &str) -> bool {
+        let source = self.source_key(source);
+        self.items.iter().any(|x| {
+            x.source == source
+                && x.article_id == article_id
+                && x.status == DownloadStatus::Completed
+        })
+    }
+
+    /// Set the identity of each source, by source name.
+    /// If the identity of a source has changed (e.g. its downloads are still keyed by name, or an explicit id has been
+    /// set), its downloads are moved to the new identity, as the history does
+    pub fn set_source_identities(&mut self, identities: HashMap<String, String>) {
+        let in_use: HashSet<&String> = identities.values().collect();
+        for (name, identity) in identities.iter() {
+            let previous = self.identities.get(name).unwrap_or(name);
+            if previous == identity || in_use.contains(previous) {
+                continue;
+            }
+            self.items
+                .iter_mut()
+                .filter(|x| &x.source == previous)
+                .for_each(|x| x.source = identity.clone());
+        }
+        self.identities = identities;
+    }
+
+    /// Get the key the downloads of the source named `name` are stored with
+    fn source_key(&self, name: &str) -> String {
+        self.identities
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Get the name of the source `download` belongs to; if the source isn't configured anymore, its identity
+    pub fn source_name<'a>(&'a self, download: &'a Download) -> &'a str {
+        self.identities
+            .iter()
+            .find(|(_, identity)| **identity == download.source)
+            .map(|(name, _)| name.as_str())
+            .unwrap_or(download.source.as_str())
+    }
+
+    /// Remove the downloads of the source with `identity`; downloaded files are kept.
+    /// Returns the removed downloads
+    pub fn remove_source(&mut self, identity: &str) -> Vec<Download> {
+        let (removed, items) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|x| x.source == identity);
+        self.items = items;
+        removed
+    }
+
+    /// Get an iterator over downloads
+    pub fn iter(&self) -> impl Iterator<Item = &Download> {
+        self.items.iter()
+    }
+
+    /// Get download at `index`
+    pub fn get(&self, index: usize) -> Option<&Download> {
+        self.items.get(index)
+    }
+}
+
+impl Download {
+    /// Create a new queued download for an enclosure of the article of the source named `source`, whose identity is
+    /// `identity`
+    pub fn new(
+        config: &DownloadsConfig,
+        source: &str,
+        identity: &str,
+        article: &Article,
+        url: &str,
+    ) -> Self {
+        let title = article.title.clone().unwrap_or_else(|| article.id.clone());
+        Self {
+            source: identity.to_string(),
+            article_id: article.id.clone(),
+            path: download_path(config, source, article, url),
+            title,
+            url: url.to_string(),
+            status: DownloadStatus::Queued,
+        }
+    }
+}
+
+/// Returns the maximum amount of concurrent downloads
+pub fn max_workers(config: &DownloadsConfig) -> usize {
+    config.workers.unwrap_or(DEFAULT_WORKERS).max(1)
+}
+
+/// Get the destination path of the enclosure at `url`, based on the configured directory and template
+fn download_path(config: &DownloadsConfig, source: &str, article: &Article, url: &str) -> PathBuf {
+    let directory = config
+        .directory
+        .clone()
+        .or_else(|| dirs::download_dir().map(|x| x.join("tuifeed")))
+        .unwrap_or_else(|| PathBuf::from("tuifeed"));
+    let date = article
+        .date
+        .unwrap_or_else(chrono::Local::now)
+        .format("%Y-%m-%d")
+        .to_string();
+    let title = article.title.as_deref().unwrap_or(article.id.as_str());
+    let template = config.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    let mut path = directory.clone();
+    // components are sanitized one by one, so the template can contain directories; `..` and absolute components
+    // are sanitized too, so the path can't leave the directory
+    for component in template.split(['/', '\\']) {
+        let component = sanitize(
+            &component
+                .replace("{feed}", &sanitize(source))
+                .replace("{date}", &date)
+                .replace("{title}", &sanitize(title)),
+        );
+        if !component.is_empty() {
+            path.push(component);
+        }
+    }
+    if path == directory {
+        path.push(
+            Some(sanitize(&article.id))
+                .filter(|x| !x.is_empty())
+                .unwrap_or_else(|| String::from("enclosure")),
+        );
+    }
+    match extension(url) {
+        Some(extension) => {
+            let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(".");
+            file_name.push(extension);
+            path.set_file_name(file_name);
+            path
+        }
+        None => path,
+    }
+}
+
+/// Get the file extension from the url path; too long extensions are not file extensions
+fn extension(url: &str) -> Option<String> {
+    open_helpers::url_extension(url).filter(|x| x.len() <= 5)
+}
+
+/// Replace characters which are not allowed in file names
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect::<String>()
+        .trim()
+        .trim_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+
+    use chrono::{Local, TimeZone};
+    use pretty_assertions::assert_eq;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn should_make_download_path() {
+        let config = DownloadsConfig {
+            directory: Some(PathBuf::from("/tmp/podcasts")),
+            template: None,
+            workers: None,
+        };
+        let article = article("1", Some("Episode 1: the beginning"));
+        assert_eq!(
+            download_path(
+                &config,
+                "My podcast",
+                &article,
+                "https://example.com/ep1.mp3?token=1"
+            ),
+            PathBuf::from("/tmp/podcasts/My podcast/2025-03-21 - Episode 1_ the beginning.mp3")
+        );
+        let config = DownloadsConfig {
+            directory: Some(PathBuf::from("/tmp/podcasts")),
+            template: Some(String::from("{title}")),
+            workers: None,
+        };
+        assert_eq!(
+            download_path(&config, "My podcast", &article, "https://example.com/ep1"),
+            PathBuf::from("/tmp/podcasts/Episode 1_ the beginning")
+        );
+    }
+
+    #[test]
+    fn should_keep_download_path_in_directory() {
+        let config = DownloadsConfig {
+            directory: Some(PathBuf::from("/tmp/podcasts")),
+            template: Some(String::from("/../{feed}/../../{title}")),
+            workers: None,
+        };
+        assert_eq!(
+            download_path(
+                &config,
+                "..",
+                &article("1", Some("..")),
+                "https://example.com/ep1.mp3"
+            ),
+            PathBuf::from("/tmp/podcasts/1.mp3")
+        );
+        let config = DownloadsConfig {
+            directory: Some(PathBuf::from("/tmp/podcasts")),
+            template: Some(String::from("C:\\{feed}\\{title}")),
+            workers: None,
+        };
+        assert_eq!(
+            download_path(
+                &config,
+                "feed",
+                &article("1", Some("Episode 1")),
+                "https://example.com/ep1.mp3"
+            ),
+            PathBuf::from("/tmp/podcasts/C_/feed/Episode 1.mp3")
+        );
+    }
+
+    #[test]
+    fn should_keep_corrupt_downloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("downloads.json");
+        std::fs::write(&path, "{\"items\":").unwrap();
+        let mut downloads = Downloads::load(&path).unwrap();
+        assert!(downloads.take_warning().is_some());
+        assert!(downloads.next_queued().is_none());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("downloads.json.corrupt")).unwrap(),
+            "{\"items\":"
+        );
+    }
+
+    #[test]
+    fn should_enqueue_downloads() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut downloads = Downloads::load(temp.path()).unwrap();
+        let download = download("https://example.com/ep1.mp3");
+        assert!(downloads.enqueue(download.clone()));
+        assert!(!downloads.enqueue(download.clone()));
+        assert_eq!(downloads.next_queued(), Some(&download));
+
+        downloads.set_status(&download.url, DownloadStatus::Failed(String::from("404")));
+        assert!(downloads.next_queued().is_none());
+        // failed downloads can be queued again
+        assert!(downloads.enqueue(download.clone()));
+        assert!(downloads.next_queued().is_some());
+
+        downloads.set_status(&download.url, DownloadStatus::Completed);
+        assert!(downloads.is_downloaded("podcast", "1"));
+        assert!(!downloads.is_downloaded("podcast", "2"));
+        assert!(downloads.remove(&download.url).is_some());
+        assert!(!downloads.is_downloaded("podcast", "1"));
+
+        downloads.enqueue(download.clone());
+        assert_eq!(downloads.remove_source("other"), vec![]);
+        assert_eq!(downloads.remove_source("podcast"), vec![download]);
+        assert!(downloads.next_queued().is_none());
+    }
+
+    #[test]
+    fn should_key_downloads_by_source_identity() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut downloads = Downloads::load(temp.path()).unwrap();
+        // downloads queued before sources had an identity are keyed by name
+        let download = download("https://example.com/ep1.mp3");
+        downloads.enqueue(download.clone());
+        downloads.set_status(&download.url, DownloadStatus::Completed);
+        downloads.set_source_identities(HashMap::from([(
+            String::from("podcast"),
+            String::from("example.com/feed"),
+        )]));
+        assert!(downloads.is_downloaded("podcast", "1"));
+        assert_eq!(
+            downloads.get(0).unwrap().source.as_str(),
+            "example.com/feed"
+        );
+        assert_eq!(downloads.source_name(downloads.get(0).unwrap()), "podcast");
+
+        // renamed sources keep their downloads
+        downloads.set_source_identities(HashMap::from([(
+            String::from("my podcast"),
+            String::from("example.com/feed"),
+        )]));
+        assert!(downloads.is_downloaded("my podcast", "1"));
+        assert!(!downloads.is_downloaded("podcast", "1"));
+        assert_eq!(
+            downloads.source_name(downloads.get(0).unwrap()),
+            "my podcast"
+        );
+        assert_eq!(downloads.remove_source("example.com/feed").len(), 1);
+    }
+
+    #[test]
+    fn should_resume_running_downloads_after_load() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut downloads = Downloads::load(temp.path()).unwrap();
+        let download = download("https://example.com/ep1.mp3");
+        downloads.enqueue(download.clone());
+        downloads.set_status(&download.url, DownloadStatus::Running);
+        downloads.save().unwrap();
+
+        let downloads = Downloads::load(temp.path()).unwrap();
+        assert_eq!(downloads.next_queued(), Some(&download));
+    }
+
+    fn download(url: &str) -> Download {
+        Download {
+            source: String::from("podcast"),
+            article_id: String::from("1"),
+            title: String::from("Episode 1"),
+            url: url.to_string(),
+            path: PathBuf::from("/tmp/ep1.mp3"),
+            status: DownloadStatus::Queued,
+        }
+    }
+
+    fn article(id: &str, title: Option<&str>) -> Article {
+        Article {
+            title: title.map(|x| x.to_string()),
+            url: "http://example.com".to_string(),
+            date: Some(Local.with_ymd_and_hms(2025, 3, 21, 16, 0, 0).unwrap()),
+            ..Article::mock(id)
+        }
+    }
+}
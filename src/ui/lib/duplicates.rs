@@ -0,0 +1,411 @@
+//! # Duplicates
+//!
+//! Detection of the same article published by different sources
+
+use std::collections::HashMap;
+
+use super::Kiosk;
+pub use crate::config::MERGED_FEED_NAME;
+use crate::config::{DuplicateMatch, DuplicatesConfig};
+use crate::feed::{Article, Feed, normalize_url};
+
+/// Default minimum similarity between titles of duplicate articles
+const DEFAULT_TITLE_SIMILARITY: f64 = 0.8;
+
+/// Groups of duplicate articles across sources, and the feed where duplicates are merged into a single article
+#[derive(Debug)]
+pub struct Duplicates {
+    /// Group of each article, by source name and article id
+    groups: HashMap<(String, String), usize>,
+    /// Articles of each group, as source name and article id
+    members: Vec<Vec<(String, String)>>,
+    /// Feed with an article for each group
+    merged: Feed,
+    /// Group of each article of the merged feed
+    merged_groups: Vec<usize>,
+}
+
+impl Default for Duplicates {
+    fn default() -> Self {
+        Self {
+            groups: HashMap::new(),
+            members: Vec::new(),
+            merged: Feed {
+                name: MERGED_FEED_NAME.to_string(),
+                articles: Vec::new(),
+            },
+            merged_groups: Vec::new(),
+        }
+    }
+}
+
+impl Duplicates {
+    /// Find the duplicate articles among the feeds in `kiosk`
+    pub fn new(config: &DuplicatesConfig, kiosk: &Kiosk) -> Self {
+        let mut sources = kiosk.sources();
+        sources.sort();
+        let articles: Vec<(&String, &Article)> = sources
+            .into_iter()
+            .filter_map(|name| kiosk.get_feed(name).map(|feed| (name, feed)))
+            .flat_map(|(name, feed)| feed.articles().map(move |article| (name, article)))
+            .collect();
+
+        let mut groups = UnionFind::new(articles.len());
+        for key in config.match_by.iter() {
+            match key {
+                DuplicateMatch::Guid => {
+                    group_by_key(&articles, &mut groups, |article| {
+                        Some(article.id.clone()).filter(|x| !x.is_empty())
+                    });
+                }
+                DuplicateMatch::Url => {
+                    group_by_key(&articles, &mut groups, |article| {
+                        normalize_url(&article.url)
+                    });
+                }
+                DuplicateMatch::Title => {
+                    let threshold = config.title_similarity.unwrap_or(DEFAULT_TITLE_SIMILARITY);
+                    group_by_title(&articles, &mut groups, threshold);
+                }
+            }
+        }
+
+        let mut duplicates = Self::default();
+        let mut group_index: HashMap<usize, usize> = HashMap::new();
+        for (i, (name, article)) in articles.iter().enumerate() {
+            let group = *group_index.entry(groups.find(i)).or_insert_with(|| {
+                duplicates.members.push(Vec::new());
+                duplicates.merged.articles.push((*article).clone());
+                duplicates.merged_groups.push(duplicates.members.len() - 1);
+                duplicates.members.len() - 1
+            });
+            duplicates.members[group].push((name.to_string(), article.id.clone()));
+            duplicates
+                .groups
+                .insert((name.to_string(), article.id.clone()), group);
+        }
+        // sort merged articles as the articles of a feed
+        let mut merged: Vec<(Article, usize)> = std::mem::take(&mut duplicates.merged.articles)
+            .into_iter()
+            .zip(std::mem::take(&mut duplicates.merged_groups))
+            .collect();
+        merged.sort_by_key(|(article, _)| std::cmp::Reverse(article.date));
+        (duplicates.merged.articles, duplicates.merged_groups) = merged.into_iter().unzip();
+
+        duplicates
+    }
+
+    /// Get the duplicates of the article with `id` of `source` in the other sources, as source name and article id
+    pub fn get(&self, source: &str, id: &str) -> Vec<(&str, &str)> {
+        self.groups
+            .get(&(source.to_string(), id.to_string()))
+            .map(|group| {
+                self.members[*group]
+                    .iter()
+                    .filter(|(x, _)| x != source)
+                    .map(|(source, id)| (source.as_str(), id.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the feed where duplicates are merged into a single article
+    pub fn merged_feed(&self) -> &Feed {
+        &self.merged
+    }
+
+    /// Get the sources the article at `index` of the merged feed comes from, as source name and article id
+    pub fn merged_sources(&self, index: usize) -> &[(String, String)] {
+        self.merged_groups
+            .get(index)
+            .map(|group| self.members[*group].as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Union-find structure used to group articles
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        self.parents[i] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // the root is the first article, so merged articles are taken from the first source
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
+
+/// Group the articles of different sources with the same key
+fn group_by_key<F>(articles: &[(&String, &Article)], groups: &mut UnionFind, key: F)
+where
+    F: Fn(&Article) -> Option<String>,
+{
+    let mut first: HashMap<String, usize> = HashMap::new();
+    for (i, (source, article)) in articles.iter().enumerate() {
+        let Some(key) = key(article) else {
+            continue;
+        };
+        match first.get(&key) {
+            Some(j) if articles[*j].0 != *source => groups.union(*j, i),
+            Some(_) => {}
+            None => {
+                first.insert(key, i);
+            }
+        }
+    }
+}
+
+/// Group the articles of different sources whose titles are at least as similar as `threshold`.
+///
+/// Each article is compared to the first article of the existing groups, so dissimilar titles aren't grouped
+/// through a chain of similar ones. Only the groups sharing a word of the prefix of the title are compared:
+/// with the words sorted from the rarest to the most common, titles which are similar enough always do
+fn group_by_title(articles: &[(&String, &Article)], groups: &mut UnionFind, threshold: f64) {
+    let titles: Vec<Vec<String>> = articles
+        .iter()
+        .map(|(_, article)| title_words(article.title.as_deref().unwrap_or_default()))
+        .collect();
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    for word in titles.iter().flatten() {
+        *frequency.entry(word.as_str()).or_default() += 1;
+    }
+    let prefixes: Vec<Vec<&str>> = titles
+        .iter()
+        .map(|words| {
+            let mut words: Vec<&str> = words.iter().map(String::as_str).collect();
+            words.sort_by_key(|word| (frequency[word], *word));
+            words.truncate(prefix_len(words.len(), threshold));
+            words
+        })
+        .collect();
+    // first article of each group, by the words of its prefix
+    let mut representatives: HashMap<&str, Vec<usize>> = HashMap::new();
+    for i in 0..articles.len() {
+        if titles[i].is_empty() {
+            continue;
+        }
+        let mut candidates: Vec<usize> = prefixes[i]
+            .iter()
+            .filter_map(|word| representatives.get(word))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        match candidates.into_iter().find(|j| {
+            articles[*j].0 != articles[i].0 && similarity(&titles[i], &titles[*j]) >= threshold
+        }) {
+            Some(j) => groups.union(j, i),
+            None => {
+                for word in prefixes[i].iter() {
+                    representatives.entry(word).or_default().push(i);
+                }
+            }
+        }
+    }
+}
+
+/// Get the amount of words, out of `len`, of which a title at least as similar as `threshold` shares at least one.
+/// The amount is rounded up, so no similar title is missed
+fn prefix_len(len: usize, threshold: f64) -> usize {
+    let shared = (threshold.clamp(0.0, 1.0) * len as f64).floor() as usize;
+    (len + 1).saturating_sub(shared).min(len)
+}
+
+/// Get the sorted and deduplicated lowercase words of `title`
+fn title_words(title: &str) -> Vec<String> {
+    let mut words: Vec<String> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+/// Jaccard similarity between two sorted sets of words
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let total = a.len() + b.len() - common;
+    if total == 0 {
+        return 0.0;
+    }
+    common as f64 / total as f64
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ui::lib::FeedState;
+
+    #[test]
+    fn should_compare_titles() {
+        let a = title_words("Rust 2024 edition released!");
+        let b = title_words("rust 2024 edition: released");
+        assert_eq!(similarity(&a, &b), 1.0);
+        let c = title_words("Rust 2024 edition is released");
+        assert_eq!(similarity(&a, &c), 0.8);
+        assert_eq!(similarity(&a, &[]), 0.0);
+    }
+
+    #[test]
+    fn should_find_duplicates() {
+        let mut kiosk = Kiosk::default();
+        kiosk.insert_feed(
+            "aggregator",
+            FeedState::Success(feed(
+                "aggregator",
+                vec![
+                    article(
+                        "a1",
+                        "https://example.com/story?utm_medium=rss",
+                        "Big story",
+                    ),
+                    article(
+                        "a2",
+                        "https://example.com/other",
+                        "Rust 2024 edition released",
+                    ),
+                    article("guid", "https://example.com/third", "Third"),
+                ],
+            )),
+        );
+        kiosk.insert_feed(
+            "topic",
+            FeedState::Success(feed(
+                "topic",
+                vec![
+                    article("t1", "https://www.example.com/story/", "A big story"),
+                    article(
+                        "t2",
+                        "https://blog.example.com/rust",
+                        "Rust 2024 edition released!",
+                    ),
+                    article("guid", "https://blog.example.com/third", "Third, again"),
+                    article("t4", "https://example.com/unique", "Unique"),
+                ],
+            )),
+        );
+
+        let config = DuplicatesConfig {
+            match_by: vec![DuplicateMatch::Url],
+            ..Default::default()
+        };
+        let duplicates = Duplicates::new(&config, &kiosk);
+        assert_eq!(duplicates.get("aggregator", "a1"), vec![("topic", "t1")]);
+        assert!(duplicates.get("aggregator", "a2").is_empty());
+        assert_eq!(duplicates.merged_feed().articles().count(), 6);
+
+        let config = DuplicatesConfig {
+            match_by: vec![
+                DuplicateMatch::Url,
+                DuplicateMatch::Guid,
+                DuplicateMatch::Title,
+            ],
+            ..Default::default()
+        };
+        let duplicates = Duplicates::new(&config, &kiosk);
+        assert_eq!(duplicates.get("topic", "t2"), vec![("aggregator", "a2")]);
+        assert_eq!(
+            duplicates.get("topic", "guid"),
+            vec![("aggregator", "guid")]
+        );
+        assert_eq!(duplicates.merged_feed().name.as_str(), MERGED_FEED_NAME);
+        assert_eq!(duplicates.merged_feed().articles().count(), 4);
+        // merged articles list all their sources; the article is taken from the first source
+        let index = duplicates
+            .merged_feed()
+            .articles()
+            .position(|x| x.id == "a1")
+            .unwrap();
+        assert_eq!(
+            duplicates.merged_sources(index),
+            &[
+                (String::from("aggregator"), String::from("a1")),
+                (String::from("topic"), String::from("t1"))
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_chain_similar_titles() {
+        let mut kiosk = Kiosk::default();
+        for (name, title) in [
+            ("a", "one two three four"),
+            ("b", "one two three five"),
+            ("c", "one two five six"),
+        ] {
+            kiosk.insert_feed(
+                name,
+                FeedState::Success(feed(
+                    name,
+                    vec![article(name, &format!("https://{name}.example.com"), title)],
+                )),
+            );
+        }
+        let config = DuplicatesConfig {
+            match_by: vec![DuplicateMatch::Title],
+            title_similarity: Some(0.6),
+            ..Default::default()
+        };
+        let duplicates = Duplicates::new(&config, &kiosk);
+        assert_eq!(duplicates.get("a", "a"), vec![("b", "b")]);
+        // "c" is as similar to "b" as "b" is to "a", but not to "a"
+        assert!(duplicates.get("c", "c").is_empty());
+        assert_eq!(duplicates.merged_feed().articles().count(), 2);
+    }
+
+    #[test]
+    fn should_get_title_prefix_len() {
+        assert_eq!(prefix_len(5, 0.8), 2);
+        assert_eq!(prefix_len(4, 1.0), 1);
+        assert_eq!(prefix_len(4, 0.0), 4);
+        assert_eq!(prefix_len(1, 0.5), 1);
+    }
+
+    fn feed(name: &str, articles: Vec<Article>) -> Feed {
+        Feed {
+            name: name.to_string(),
+            articles,
+        }
+    }
+
+    fn article(id: &str, url: &str, title: &str) -> Article {
+        Article {
+            title: Some(title.to_string()),
+            url: url.to_string(),
+            ..Article::mock(id)
+        }
+    }
+}
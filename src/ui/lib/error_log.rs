@@ -0,0 +1,86 @@
+//! # Error log
+//!
+//! Collects the errors occurred while fetching sources
+
+use chrono::{DateTime, Local};
+
+use crate::feed::FeedError;
+
+/// Log of the errors occurred while fetching sources
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    entries: Vec<ErrorLogEntry>,
+}
+
+/// A single error in the [`ErrorLog`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLogEntry {
+    pub timestamp: DateTime<Local>,
+    /// Name of the source which failed
+    pub source: String,
+    pub error: FeedError,
+}
+
+impl ErrorLog {
+    /// Push a new error into the log
+    pub fn push(&mut self, source: &str, error: FeedError) {
+        self.entries.push(ErrorLogEntry {
+            timestamp: Local::now(),
+            source: source.to_string(),
+            error,
+        });
+    }
+
+    /// Get entry at `index`
+    pub fn get(&self, index: usize) -> Option<&ErrorLogEntry> {
+        self.entries.get(index)
+    }
+
+    /// Get an iterator over entries
+    pub fn iter(&self) -> impl Iterator<Item = &ErrorLogEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the amount of errors in the log
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all the errors from the log
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_collect_errors() {
+        let mut log = ErrorLog::default();
+        assert!(log.is_empty());
+        log.push(
+            "lefigaro",
+            FeedError::Http(String::from("http status: 404")),
+        );
+        log.push("nytimes", FeedError::Parse(String::from("no root element")));
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.get(0).unwrap().source.as_str(), "lefigaro");
+        assert_eq!(
+            log.get(1).unwrap().error,
+            FeedError::Parse(String::from("no root element"))
+        );
+        assert_eq!(log.iter().count(), 2);
+        log.clear();
+        assert!(log.is_empty());
+        assert!(log.get(0).is_none());
+    }
+}
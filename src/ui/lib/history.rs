@@ -1,12 +1,26 @@
-use std::collections::HashMap;
-use std::fs::File;
+mod stats;
+mod storage;
+mod sync;
+
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use self::storage::{HistoryChange, HistoryStorage, JsonStorage, SqliteStorage};
+pub use self::sync::HistorySync;
+use crate::config::HistoryConfig;
 use crate::feed::Article;
+use crate::helpers::path as path_helpers;
+
+/// Name of the legacy JSON history file, migrated into the database on first run
+const LEGACY_HISTORY_FILE: &str = "history.json";
+/// Default amount of days the read state of articles dropped from their feed is kept for
+const DEFAULT_RETENTION_DAYS: u64 = 90;
+/// Default maximum amount of articles dropped from their feed which are kept for each source
+const DEFAULT_RETENTION_MAX_ARTICLES: usize = 1000;
 
 /// Result type for [`History`]
 pub type HistoryResult<T> = Result<T, HistoryError>;
@@ -18,169 +32,599 @@ pub enum HistoryError {
     Io(#[from] std::io::Error),
     #[error("No cache directory found on your system")]
     NoCacheDir,
+    #[error("{0}")]
+    Profile(String),
     #[error("Serde error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("{} can't be parsed, so it is left unchanged", .0.display())]
+    Unparsable(PathBuf),
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(
+        "History format version {found} has been written by a newer version of tuifeed (supported up to version {supported})"
+    )]
+    UnsupportedVersion { found: usize, supported: usize },
 }
 
-/// History of read articles
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+impl HistoryError {
+    /// Returns whether the error is caused by a corrupt history file
+    fn is_corrupt(&self) -> bool {
+        match self {
+            Self::Serde(_) => true,
+            Self::Sqlite(err) => matches!(
+                err.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+            ),
+            Self::Io(_)
+            | Self::NoCacheDir
+            | Self::Profile(_)
+            | Self::Unparsable(_)
+            | Self::UnsupportedVersion { .. } => false,
+        }
+    }
+}
+
+/// History of read articles.
+///
+/// The whole history is kept in memory, while only the entries changed since the last save are written to the storage.
+///
+/// The history of each source is keyed by the source identity, so that sources can be renamed freely;
+/// methods take the source name, which is resolved to its identity
+#[derive(Debug, Default)]
 pub struct History {
-    #[serde(skip)]
-    path: PathBuf,
     sources: HashMap<String, SourceHistory>,
+    /// Identity of each source, by source name. Sources without an identity are keyed by name
+    identities: HashMap<String, String>,
+    /// Storage where history is persisted; if `None`, history is kept in memory only
+    storage: Option<Box<dyn HistoryStorage>>,
+    /// Sources whose whole history has changed since the last save
+    dirty_sources: HashSet<String>,
+    /// Articles which have changed since the last save, as source and article id
+    dirty_articles: HashSet<(String, String)>,
+    /// Warning raised while loading the history (e.g. the history was corrupt)
+    warning: Option<String>,
+    retention: Retention,
+}
+
+/// Describes for how long the read state of articles dropped from their feed is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Dropped articles are removed from history after this amount of seconds
+    pub max_age: u64,
+    /// Maximum amount of dropped articles kept for each source; the oldest ones are removed first
+    pub max_articles: usize,
+}
+
+impl Retention {
+    pub fn new(days: u64, max_articles: usize) -> Self {
+        Self {
+            // a retention too long to be counted in seconds keeps dropped articles forever
+            max_age: days.saturating_mul(24 * 60 * 60),
+            max_articles,
+        }
+    }
+}
+
+impl From<&HistoryConfig> for Retention {
+    fn from(config: &HistoryConfig) -> Self {
+        Self::new(
+            config.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS),
+            config
+                .retention_max_articles
+                .unwrap_or(DEFAULT_RETENTION_MAX_ARTICLES),
+        )
+    }
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETENTION_DAYS, DEFAULT_RETENTION_MAX_ARTICLES)
+    }
 }
 
 impl History {
-    /// Load [`History`] from disk
+    /// Load [`History`] from disk.
+    /// A `.json` file is loaded as JSON, any other file as a SQLite database.
+    /// If the database is empty and a legacy `history.json` exists next to it, the JSON history is migrated into it.
+    ///
+    /// Once loaded, a backup copy of the history is written next to it. If the history is corrupt, it is moved aside
+    /// and the backup is restored; the reason is reported by [`History::take_warning`]
     pub fn load(path: &Path) -> HistoryResult<Self> {
-        if !path.exists() {
-            return Ok(History {
-                path: path.to_path_buf(),
-                ..Default::default()
-            });
+        let mut history = match Self::open(path) {
+            Ok(history) => history,
+            Err(err) if err.is_corrupt() => Self::recover(path, err)?,
+            Err(err) => return Err(err),
+        };
+        if let Some(storage) = history.storage.as_mut()
+            && let Err(err) = storage.backup(&backup_path(path))
+            && history.warning.is_none()
+        {
+            history.warning = Some(format!("Could not write history backup: {err}"));
         }
 
-        let mut reader = File::open(path)?;
-        let mut history: History = serde_json::from_reader(&mut reader).unwrap_or_default();
+        Ok(history)
+    }
 
-        // save path
-        history.path = path.to_path_buf();
+    /// Open the history at `path`
+    fn open(path: &Path) -> HistoryResult<Self> {
+        if path.extension().is_some_and(|x| x == "json") {
+            return Self::with_storage(Box::new(JsonStorage::new(path)));
+        }
+        let storage = SqliteStorage::open(path)?;
+        let legacy_path = path.with_file_name(LEGACY_HISTORY_FILE);
+        if storage.is_empty()? && legacy_path.exists() {
+            return Self::migrate(&legacy_path, storage);
+        }
+
+        Self::with_storage(Box::new(storage))
+    }
+
+    /// Recover the corrupt history at `path`: the corrupt file is kept with the `.corrupt` extension,
+    /// then the backup is restored if valid, otherwise an empty history is created
+    fn recover(path: &Path, err: HistoryError) -> HistoryResult<Self> {
+        let corrupt_path = path_with_suffix(path, ".corrupt");
+        std::fs::rename(path, &corrupt_path)?;
+        let backup_path = backup_path(path);
+        if backup_path.exists() {
+            std::fs::copy(&backup_path, path)?;
+            if let Ok(mut history) = Self::open(path) {
+                history.warning = Some(format!(
+                    "History was corrupt ({err}) and has been restored from backup. The corrupt file has been kept at {}",
+                    corrupt_path.display()
+                ));
+                return Ok(history);
+            }
+            let _ = std::fs::remove_file(path);
+        }
+        let mut history = Self::open(path)?;
+        history.warning = Some(format!(
+            "History was corrupt ({err}) and no valid backup was found: all articles are unread. The corrupt file has been kept at {}",
+            corrupt_path.display()
+        ));
 
         Ok(history)
     }
 
-    /// Save [`History`] to disk
-    pub fn save(&self) -> HistoryResult<()> {
-        let mut writer = File::create(&self.path)?;
-        serde_json::to_writer(&mut writer, self)?;
+    /// Load [`History`] from the provided storage
+    fn with_storage(mut storage: Box<dyn HistoryStorage>) -> HistoryResult<Self> {
+        Ok(History {
+            sources: storage.load()?,
+            storage: Some(storage),
+            ..Default::default()
+        })
+    }
+
+    /// Migrate the legacy JSON history at `legacy_path` into `storage`.
+    /// The JSON file is then renamed to `history.json.bak`; if it is corrupt, it is renamed to `history.json.corrupt`
+    fn migrate(legacy_path: &Path, storage: SqliteStorage) -> HistoryResult<Self> {
+        let mut history = match Self::with_storage(Box::new(JsonStorage::new(legacy_path))) {
+            Ok(history) => history,
+            Err(err) => {
+                let corrupt_path = path_with_suffix(legacy_path, ".corrupt");
+                std::fs::rename(legacy_path, &corrupt_path)?;
+                let mut history = Self::with_storage(Box::new(storage))?;
+                history.warning = Some(format!(
+                    "Could not migrate the history ({err}): all articles are unread. The old history has been kept at {}",
+                    corrupt_path.display()
+                ));
+                return Ok(history);
+            }
+        };
+        history.storage = Some(Box::new(storage));
+        history.dirty_sources = history.sources.keys().cloned().collect();
+        history.save()?;
+        std::fs::rename(legacy_path, path_with_suffix(legacy_path, ".bak"))?;
+
+        Ok(history)
+    }
+
+    /// Take the warning raised while loading the history, if any
+    pub fn take_warning(&mut self) -> Option<String> {
+        self.warning.take()
+    }
+
+    /// Returns whether the history has changed since the last save
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_sources.is_empty() || !self.dirty_articles.is_empty()
+    }
+
+    /// Save the changes to [`History`] to disk
+    pub fn save(&mut self) -> HistoryResult<()> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+        let Some(storage) = self.storage.as_mut() else {
+            return Ok(());
+        };
+        let changes: Vec<HistoryChange> = self
+            .dirty_sources
+            .iter()
+            .cloned()
+            .map(HistoryChange::Source)
+            .chain(
+                self.dirty_articles
+                    .iter()
+                    .filter(|(source, _)| !self.dirty_sources.contains(source))
+                    .map(|(source, id)| HistoryChange::Article(source.clone(), id.clone())),
+            )
+            .collect();
+        storage.save(&self.sources, &changes)?;
+        self.dirty_sources.clear();
+        self.dirty_articles.clear();
 
         Ok(())
     }
 
-    /// Init path for history file
-    pub fn default_path() -> HistoryResult<PathBuf> {
+    /// Init path for history database of `profile`; `None` is the default profile
+    pub fn default_path(profile: Option<&str>) -> HistoryResult<PathBuf> {
         let path = dirs::cache_dir()
             .or(dirs::config_dir())
             .ok_or(HistoryError::NoCacheDir)?
             .join("tuifeed");
+        let path = path_helpers::profile_dir(&path, profile).map_err(HistoryError::Profile)?;
 
         // init dir
         if !path.exists() {
             std::fs::create_dir_all(&path)?;
         }
 
-        Ok(path.join("history.json"))
+        Ok(path.join("history.db"))
     }
 
-    /// Remove all articles from the history of a source, if not in the provided
-    /// [`Article`]s list.
-    pub fn filter_articles(&mut self, source: &str, articles: &[&Article]) {
-        let source = self
-            .sources
-            .entry(source.to_string())
-            .or_insert_with(|| SourceHistory {
-                feed: HashMap::new(),
-            });
+    /// Export the whole history to a portable JSON file at `path`
+    pub fn export(&self, path: &Path) -> HistoryResult<()> {
+        JsonStorage::new(path).save(&self.sources, &[])
+    }
 
-        // remove all articles not in the provided list
-        source
-            .feed
-            .retain(|article_id, _| articles.iter().any(|a| &a.id == article_id));
+    /// Merge the history exported to `path` into this history.
+    /// Returns the amount of articles which have changed
+    pub fn import(&mut self, path: &Path) -> HistoryResult<usize> {
+        if !path.exists() {
+            return Err(HistoryError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} doesn't exist", path.display()),
+            )));
+        }
+        let sources = JsonStorage::new(path).load()?;
+
+        Ok(self.merge(sources))
     }
 
-    /// Insert an article into the history
-    pub fn insert(&mut self, source: &str, article: &Article) {
-        let source = self
-            .sources
-            .entry(source.to_string())
-            .or_insert_with(|| SourceHistory {
-                feed: HashMap::new(),
-            });
+    /// Merge `sources` into this history, article by article: the most recent read state wins, according to the time
+    /// the article has been last viewed. Articles missing in this history are added.
+    /// Returns the amount of articles whose read state has changed
+    fn merge(&mut self, sources: HashMap<String, SourceHistory>) -> usize {
+        let mut changed = 0;
+        for (source_name, source) in sources {
+            // histories exported before sources had an identity are keyed by name
+            let source_name = self.source_key(&source_name);
+            let history = self.sources.entry(source_name.clone()).or_default();
+            for (id, article) in source.feed {
+                let was_read = match history.feed.get_mut(&id) {
+                    Some(entry) if entry.last_viewed >= article.last_viewed => continue,
+                    Some(entry) => {
+                        let was_read = entry.is_read();
+                        entry.last_viewed = article.last_viewed;
+                        entry.unread = article.unread;
+                        entry.updated = article.updated;
+                        entry.dismissed = article.dismissed;
+                        was_read
+                    }
+                    None => {
+                        history.feed.insert(id.clone(), article);
+                        false
+                    }
+                };
+                if history
+                    .feed
+                    .get(&id)
+                    .is_some_and(|x| x.is_read() != was_read)
+                {
+                    changed += 1;
+                }
+                self.dirty_articles.insert((source_name.clone(), id));
+            }
+        }
+        changed
+    }
 
-        let article_time = article
-            .date
-            .map(|date| date.into())
-            .unwrap_or_else(SystemTime::now);
+    /// Set the identity of each source, by source name.
+    /// If the identity of a source has changed (e.g. its history is still keyed by name, or an explicit id has been
+    /// set), its history is moved to the new identity, unless there is a history for the new identity already
+    pub fn set_source_identities(&mut self, identities: HashMap<String, String>) {
+        let in_use: HashSet<&String> = identities.values().collect();
+        for (name, identity) in identities.iter() {
+            let previous = self.identities.get(name).unwrap_or(name);
+            if previous == identity
+                || in_use.contains(previous)
+                || self.sources.contains_key(identity)
+            {
+                continue;
+            }
+            if let Some(history) = self.sources.remove(previous) {
+                self.dirty_sources.insert(previous.clone());
+                self.dirty_sources.insert(identity.clone());
+                self.sources.insert(identity.clone(), history);
+            }
+        }
+        self.identities = identities;
+    }
 
-        source
+    /// Get the key the history of the source named `name` is stored with
+    fn source_key(&self, name: &str) -> String {
+        self.identities
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Set the retention policy for articles dropped from their feed
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+    }
+
+    /// Mark the articles of a source which are not in the provided [`Article`]s list as dropped from the feed,
+    /// then remove the dropped articles exceeding the retention policy.
+    /// Articles which are back in the feed are not dropped anymore
+    pub fn filter_articles(&mut self, source_name: &str, articles: &[&Article]) {
+        let source_name = self.source_key(source_name);
+        let source = self.sources.entry(source_name.clone()).or_default();
+        let ids: HashSet<&str> = articles.iter().map(|a| a.id.as_str()).collect();
+        let now = now();
+
+        for (article_id, article) in source.feed.iter_mut() {
+            let in_feed = ids.contains(article_id.as_str());
+            if in_feed == article.dropped_at.is_none() {
+                continue;
+            }
+            article.dropped_at = if in_feed { None } else { Some(now) };
+            self.dirty_articles
+                .insert((source_name.to_string(), article_id.clone()));
+        }
+        self.collect_garbage(&source_name, now);
+    }
+
+    /// Mark the articles of the sources which aren't configured anymore as dropped from their feed, then remove the
+    /// dropped articles exceeding the retention policy. Until then, the history of a removed source is kept, in case
+    /// it's added back
+    pub fn drop_removed_sources(&mut self) {
+        let configured: HashSet<&String> = self.identities.values().collect();
+        let removed: Vec<String> = self
+            .sources
+            .keys()
+            .filter(|x| !configured.contains(x))
+            .cloned()
+            .collect();
+        let now = now();
+        for source_name in removed {
+            let Some(source) = self.sources.get_mut(&source_name) else {
+                continue;
+            };
+            for (article_id, article) in source.feed.iter_mut() {
+                if article.dropped_at.is_none() {
+                    article.dropped_at = Some(now);
+                    self.dirty_articles
+                        .insert((source_name.clone(), article_id.clone()));
+                }
+            }
+            self.collect_garbage(&source_name, now);
+            if self
+                .sources
+                .get(&source_name)
+                .is_some_and(|x| x.feed.is_empty())
+            {
+                self.sources.remove(&source_name);
+            }
+        }
+    }
+
+    /// Remove the articles of `source_name` dropped too long ago, then the oldest ones exceeding the size cap
+    fn collect_garbage(&mut self, source_name: &str, now: u64) {
+        let Some(source) = self.sources.get_mut(source_name) else {
+            return;
+        };
+        let mut dropped: Vec<(u64, String)> = source
             .feed
-            .entry(article.id.clone())
-            .or_insert_with(|| ArticleHistory {
-                timestamp: timestamp(article_time),
-                last_viewed: None,
-            });
+            .iter()
+            .filter_map(|(id, article)| article.dropped_at.map(|t| (t, id.clone())))
+            .collect();
+        dropped.sort_unstable_by(|a, b| b.cmp(a));
+        let expired = dropped.iter().enumerate().filter(|(i, (dropped_at, _))| {
+            *i >= self.retention.max_articles
+                || now.saturating_sub(*dropped_at) > self.retention.max_age
+        });
+        for (_, (_, id)) in expired {
+            source.feed.remove(id);
+            self.dirty_articles
+                .insert((source_name.to_string(), id.clone()));
+        }
+    }
+
+    /// Insert an article into the history.
+    /// If the article exists already and its content or update time has changed since it has been viewed,
+    /// the article is flagged as updated; if `updates_unread` is true, it is marked as unread too, but it is still marked as
+    /// read when viewed, unlike articles which have been explicitly marked as unread
+    pub fn insert(&mut self, source_name: &str, article: &Article, updates_unread: bool) {
+        let source_name = self.source_key(source_name);
+        let source = self.sources.entry(source_name.clone()).or_default();
+        let Some(entry) = source.feed.get_mut(&article.id) else {
+            source
+                .feed
+                .insert(article.id.clone(), ArticleHistory::new(article));
+            self.dirty_articles
+                .insert((source_name, article.id.clone()));
+            return;
+        };
+
+        let fingerprint = article.fingerprint();
+        let updated_at = article.updated.map(|date| timestamp(date.into()));
+        if entry.fingerprint == Some(fingerprint) && entry.updated_at == updated_at {
+            return;
+        }
+        // entries stored before fingerprints were introduced have no fingerprint to compare with
+        let changed = entry.fingerprint.is_some_and(|x| x != fingerprint)
+            || entry
+                .updated_at
+                .zip(updated_at)
+                .is_some_and(|(old, new)| new > old);
+        if changed && entry.is_read() {
+            entry.updated = true;
+            if updates_unread {
+                entry.last_viewed = None;
+            }
+        }
+        entry.fingerprint = Some(fingerprint);
+        entry.updated_at = updated_at;
+        self.dirty_articles
+            .insert((source_name, article.id.clone()));
     }
 
     /// set an article as read
     pub fn read(&mut self, source: &str, article: &Article) {
-        // get entry
-        let source = self
-            .sources
-            .entry(source.to_string())
-            .or_insert_with(|| SourceHistory {
-                feed: HashMap::new(),
-            });
+        self.article_entry(source, article).read();
+    }
 
-        let article_time = article
-            .date
-            .map(|date| date.into())
-            .unwrap_or_else(SystemTime::now);
+    /// set an article as read because it has been viewed, unless it has been explicitly marked as unread.
+    /// Returns whether the article has been marked as read
+    pub fn view(&mut self, source: &str, article: &Article) -> bool {
+        let entry = self.article_entry(source, article);
+        if entry.unread {
+            false
+        } else {
+            entry.read();
+            true
+        }
+    }
+
+    /// toggle the read state of an article.
+    /// Returns whether the article is now read
+    pub fn toggle(&mut self, source: &str, article: &Article) -> bool {
+        let entry = self.article_entry(source, article);
+        if entry.is_read() {
+            entry.unread();
+            false
+        } else {
+            entry.read();
+            true
+        }
+    }
+
+    /// set all articles from a source as unread.
+    /// Returns the amount of articles which were read
+    pub fn unread_source(&mut self, source_name: &str) -> usize {
+        let source_name = self.source_key(source_name);
+        let Some(source) = self.sources.get_mut(&source_name) else {
+            return 0;
+        };
+        let mut read = 0;
+        for (id, article) in source.feed.iter_mut().filter(|(_, x)| !x.is_dropped()) {
+            if article.is_read() {
+                read += 1;
+            }
+            article.unread();
+            self.dirty_articles
+                .insert((source_name.clone(), id.clone()));
+        }
+        read
+    }
+
+    /// Get the history entry for an article, inserting it if missing.
+    /// The entry is marked as changed
+    fn article_entry(&mut self, source: &str, article: &Article) -> &mut ArticleHistory {
+        let source = self.source_key(source);
+        self.dirty_articles
+            .insert((source.clone(), article.id.clone()));
+        let source = self.sources.entry(source).or_default();
 
-        // update article
         source
             .feed
             .entry(article.id.clone())
-            .or_insert_with(|| ArticleHistory {
-                timestamp: timestamp(article_time),
-                last_viewed: None,
-            })
-            .last_viewed = Some(now());
+            .or_insert_with(|| ArticleHistory::new(article))
     }
 
-    /// set all articles from a source as read
-    pub fn read_source(&mut self, source: &str) {
+    /// set all articles from a source as read.
+    /// Returns the amount of articles which were unread
+    pub fn read_source(&mut self, source_name: &str) -> usize {
         // get entry
-        let source = self
-            .sources
-            .entry(source.to_string())
-            .or_insert_with(|| SourceHistory {
-                feed: HashMap::new(),
-            });
+        let source_name = self.source_key(source_name);
+        let source = self.sources.entry(source_name.clone()).or_default();
 
         // update all articles
-        for article in source.feed.values_mut() {
-            article.last_viewed = Some(now());
+        let mut unread = 0;
+        for (id, article) in source.feed.iter_mut().filter(|(_, x)| !x.is_dropped()) {
+            if !article.is_read() {
+                unread += 1;
+            }
+            article.dismiss();
+            self.dirty_articles
+                .insert((source_name.clone(), id.clone()));
         }
+        unread
     }
 
-    /// set all articles as read
-    pub fn read_all(&mut self) {
+    /// set all articles as read.
+    /// Returns the amount of articles which were unread
+    pub fn read_all(&mut self) -> usize {
         // update all articles
-        for source in self.sources.values_mut() {
-            for article in source.feed.values_mut() {
-                article.last_viewed = Some(now());
+        let mut unread = 0;
+        for (source_name, source) in self.sources.iter_mut() {
+            for (id, article) in source.feed.iter_mut().filter(|(_, x)| !x.is_dropped()) {
+                if !article.is_read() {
+                    unread += 1;
+                }
+                article.dismiss();
+                self.dirty_articles
+                    .insert((source_name.clone(), id.clone()));
             }
         }
+        unread
     }
 
     /// Returns whether the article has been read
     pub fn is_article_read(&self, source: &str, article: &Article) -> bool {
         self.sources
-            .get(source)
+            .get(&self.source_key(source))
             .and_then(|source| source.feed.get(&article.id))
             .map(|article| article.is_read())
             .unwrap_or_default()
     }
 
+    /// Returns whether the article has changed since it has been viewed
+    pub fn is_article_updated(&self, source: &str, article: &Article) -> bool {
+        self.sources
+            .get(&self.source_key(source))
+            .and_then(|source| source.feed.get(&article.id))
+            .is_some_and(|article| article.updated)
+    }
+
     /// Returns whether all articles from a source have been read.
+    /// Articles dropped from the feed are not considered
     pub fn is_source_read(&self, source: &str) -> bool {
         self.sources
-            .get(source)
-            .map(|source| source.feed.values().all(|article| article.is_read()))
+            .get(&self.source_key(source))
+            .map(|source| {
+                source
+                    .feed
+                    .values()
+                    .filter(|article| !article.is_dropped())
+                    .all(|article| article.is_read())
+            })
             .unwrap_or_default()
     }
 }
 
+/// Get the path of the backup of the history at `path`
+fn backup_path(path: &Path) -> PathBuf {
+    path_with_suffix(path, ".bak")
+}
+
+/// Append `suffix` to the file name of `path`
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
 fn now() -> u64 {
     timestamp(SystemTime::now())
 }
@@ -191,7 +635,7 @@ fn timestamp(t: SystemTime) -> u64 {
         .as_secs()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 struct SourceHistory {
     feed: HashMap<String, ArticleHistory>,
 }
@@ -199,21 +643,94 @@ struct SourceHistory {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ArticleHistory {
     timestamp: u64,
+    /// Last time the article has been viewed or its read state has been changed
     last_viewed: Option<u64>,
+    /// The article has been explicitly marked as unread, so it must not be marked as read when viewed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    unread: bool,
+    /// Time the article has been dropped from its feed; it is kept until it exceeds the retention policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dropped_at: Option<u64>,
+    /// Fingerprint of the article content, as last fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<u64>,
+    /// Update time reported by the feed, as last fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<u64>,
+    /// The article has changed since it has been viewed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    updated: bool,
+    /// The article has been marked as read along with its whole source, without being viewed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    dismissed: bool,
 }
 
 impl ArticleHistory {
+    fn new(article: &Article) -> Self {
+        let article_time = article
+            .date
+            .map(|date| date.into())
+            .unwrap_or_else(SystemTime::now);
+
+        Self {
+            timestamp: timestamp(article_time),
+            last_viewed: None,
+            unread: false,
+            dropped_at: None,
+            fingerprint: Some(article.fingerprint()),
+            updated_at: article.updated.map(|date| timestamp(date.into())),
+            updated: false,
+            dismissed: false,
+        }
+    }
+
+    /// Returns whether the article has been dropped from its feed
+    fn is_dropped(&self) -> bool {
+        self.dropped_at.is_some()
+    }
+
     /// Returns whether the article has been read
     fn is_read(&self) -> bool {
-        self.last_viewed
-            .map(|t| t >= self.timestamp)
-            .unwrap_or_default()
+        !self.unread
+            && self
+                .last_viewed
+                .map(|t| t >= self.timestamp)
+                .unwrap_or_default()
+    }
+
+    /// Mark article as read, clearing the unread override and the updated flag
+    fn read(&mut self) {
+        self.last_viewed = Some(now());
+        self.unread = false;
+        self.updated = false;
+        self.dismissed = false;
+    }
+
+    /// Mark article as read without viewing it, as when its whole source is marked as read.
+    /// Articles which have already been read keep the time they have been viewed
+    fn dismiss(&mut self) {
+        if self.is_read() {
+            self.updated = false;
+        } else {
+            self.read();
+            self.dismissed = true;
+        }
+    }
+
+    /// Mark article as unread.
+    /// The time is kept in `last_viewed`, so that the most recent read state wins when histories are merged
+    fn unread(&mut self) {
+        self.last_viewed = Some(now());
+        self.unread = true;
+        self.dismissed = false;
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use chrono::Local;
+    use pretty_assertions::assert_eq;
     use tempfile::NamedTempFile;
 
     use super::*;
@@ -232,8 +749,11 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
         // mark read
@@ -250,8 +770,11 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
         assert!(!history.is_article_read(&source, &unread_article));
 
@@ -272,11 +795,14 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source, &article);
+        history.insert(&source, &article, false);
         assert!(!history.is_article_read(&source, &article));
 
         history.save().expect("save history");
@@ -296,8 +822,11 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
         let article2 = Article {
@@ -305,16 +834,19 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source, &article);
-        history.insert(&source, &article2);
+        history.insert(&source, &article, false);
+        history.insert(&source, &article2, false);
 
         // filter articles
         history.filter_articles(&source, &[&article]);
-        assert!(history.sources.get(&source).is_some());
+        assert!(history.sources.contains_key(&source));
         assert!(
             history
                 .sources
@@ -323,14 +855,60 @@ mod test {
                 .feed
                 .contains_key(&article.id)
         );
-        assert!(
-            !history
+        // dropped articles are kept
+        let feed = &history.sources.get(&source).unwrap().feed;
+        assert!(feed.get(&article.id).unwrap().dropped_at.is_none());
+        assert!(feed.get(&article2.id).unwrap().dropped_at.is_some());
+        assert!(!history.is_source_read(&source));
+        history.read(&source, &article);
+        assert!(history.is_source_read(&source));
+
+        // article is back in the feed
+        history.filter_articles(&source, &[&article, &article2]);
+        let feed = &history.sources.get(&source).unwrap().feed;
+        assert!(feed.get(&article2.id).unwrap().dropped_at.is_none());
+    }
+
+    #[test]
+    fn test_should_collect_dropped_articles() {
+        let mut history = History::default();
+        let articles: Vec<Article> = (0..5).map(|i| article(&i.to_string())).collect();
+        for article in articles.iter() {
+            history.read("figaro", article);
+        }
+        // drop articles at different times
+        history.filter_articles("figaro", &[&articles[0]]);
+        for (i, id) in ["1", "2", "3", "4"].into_iter().enumerate() {
+            let article = history
                 .sources
-                .get(&source)
+                .get_mut("figaro")
                 .unwrap()
                 .feed
-                .contains_key(&article2.id)
-        );
+                .get_mut(id)
+                .unwrap();
+            article.dropped_at = Some(now() - 60 * 60 * 24 * (i as u64 * 50));
+        }
+        history.set_retention(Retention::new(90, 2));
+        history.filter_articles("figaro", &[&articles[0]]);
+        // articles 3 and 4 have been dropped more than 90 days ago
+        let feed = &history.sources.get("figaro").unwrap().feed;
+        let mut ids: Vec<&String> = feed.keys().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["0", "1", "2"]);
+
+        // only the most recently dropped article is kept
+        history.set_retention(Retention::new(90, 1));
+        history.filter_articles("figaro", &[&articles[0]]);
+        let feed = &history.sources.get("figaro").unwrap().feed;
+        let mut ids: Vec<&String> = feed.keys().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["0", "1"]);
+    }
+
+    #[test]
+    fn should_not_overflow_retention() {
+        assert_eq!(Retention::new(1, 10).max_age, 86400);
+        assert_eq!(Retention::new(u64::MAX, 10).max_age, u64::MAX);
     }
 
     #[test]
@@ -347,11 +925,14 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source, &article);
+        history.insert(&source, &article, false);
         assert!(!history.is_source_read(&source));
 
         let source2 = "lemonde".to_string();
@@ -360,19 +941,64 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source2, &article);
+        history.insert(&source2, &article, false);
         assert!(!history.is_source_read(&source2));
 
-        history.read_source(&source);
+        assert_eq!(history.read_source(&source), 1);
         assert!(history.is_source_read(&source));
         assert!(!history.is_source_read(&source2));
         assert!(history.is_article_read(&source, &article));
     }
 
+    #[test]
+    fn test_should_mark_articles_as_unread() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut history = History::load(temp.path()).expect("load history");
+        let source = "figaro".to_string();
+        let article = Article {
+            id: "1".to_string(),
+            title: Some("title".to_string()),
+            authors: vec![],
+            date: None,
+            updated: None,
+            summary: String::default(),
+            links: vec![],
+            url: "http://example.com".to_string(),
+            enclosures: vec![],
+        };
+
+        assert!(history.view(&source, &article));
+        assert!(history.is_article_read(&source, &article));
+        assert_eq!(history.toggle(&source, &article), false);
+        assert!(!history.is_article_read(&source, &article));
+        // explicitly unread articles are not read when viewed
+        assert!(!history.view(&source, &article));
+        assert!(!history.is_article_read(&source, &article));
+        assert_eq!(history.toggle(&source, &article), true);
+        assert!(history.is_article_read(&source, &article));
+
+        // unread override is persisted
+        assert_eq!(history.toggle(&source, &article), false);
+        history.save().expect("save history");
+        let mut history = History::load(temp.path()).expect("load history");
+        assert!(!history.is_article_read(&source, &article));
+        assert!(!history.view(&source, &article));
+
+        // mark source as read and unread
+        assert_eq!(history.read_source(&source), 1);
+        assert!(history.is_article_read(&source, &article));
+        assert_eq!(history.unread_source(&source), 1);
+        assert!(!history.is_source_read(&source));
+        assert_eq!(history.unread_source("lemonde"), 0);
+    }
+
     #[test]
     fn test_should_read_all_sources() {
         let temp = NamedTempFile::new().unwrap();
@@ -387,11 +1013,14 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source, &article);
+        history.insert(&source, &article, false);
         assert!(!history.is_source_read(&source));
 
         let source2 = "lemonde".to_string();
@@ -400,16 +1029,277 @@ mod test {
             title: Some("title".to_string()),
             authors: vec![],
             date: None,
+            updated: None,
             summary: String::default(),
+            links: vec![],
             url: "http://example.com".to_string(),
+            enclosures: vec![],
         };
 
-        history.insert(&source2, &article);
+        history.insert(&source2, &article, false);
         assert!(!history.is_source_read(&source2));
 
-        history.read_all();
+        assert_eq!(history.read_all(), 2);
         assert!(history.is_source_read(&source));
         assert!(history.is_source_read(&source2));
         assert!(history.is_article_read(&source, &article));
     }
+
+    #[test]
+    fn test_should_rename_source() {
+        let mut history = History::default();
+        let article = Article {
+            id: "1".to_string(),
+            title: Some("title".to_string()),
+            authors: vec![],
+            date: None,
+            updated: None,
+            summary: String::default(),
+            links: vec![],
+            url: "http://example.com".to_string(),
+            enclosures: vec![],
+        };
+
+        // history keyed by name is migrated to the source identity
+        history.read("figaro", &article);
+        history.set_source_identities(HashMap::from([(
+            String::from("figaro"),
+            String::from("lefigaro.fr/rss"),
+        )]));
+        assert!(history.sources.contains_key("lefigaro.fr/rss"));
+        assert!(!history.sources.contains_key("figaro"));
+        assert!(history.is_article_read("figaro", &article));
+        // renamed sources keep their history
+        history.set_source_identities(HashMap::from([(
+            String::from("lefigaro"),
+            String::from("lefigaro.fr/rss"),
+        )]));
+        assert!(history.is_article_read("lefigaro", &article));
+        assert!(!history.is_article_read("figaro", &article));
+        // setting an explicit id moves the history too
+        history.set_source_identities(HashMap::from([(
+            String::from("lefigaro"),
+            String::from("figaro"),
+        )]));
+        assert!(history.sources.contains_key("figaro"));
+        assert!(history.is_article_read("lefigaro", &article));
+    }
+
+    #[test]
+    fn should_drop_removed_sources() {
+        let mut history = History::default();
+        let article = article("1");
+        history.insert("lefigaro", &article, false);
+        history.read("lefigaro", &article);
+        history.insert("lemonde", &article, false);
+        history.set_source_identities(HashMap::from([(
+            String::from("lemonde"),
+            String::from("lemonde"),
+        )]));
+        history.set_retention(Retention::new(90, 10));
+
+        // the history of the removed source is kept until it exceeds the retention policy
+        history.drop_removed_sources();
+        assert!(history.is_article_read("lefigaro", &article));
+        assert!(
+            history.sources.get("lemonde").unwrap().feed["1"]
+                .dropped_at
+                .is_none()
+        );
+        history
+            .sources
+            .get_mut("lefigaro")
+            .unwrap()
+            .feed
+            .get_mut("1")
+            .unwrap()
+            .dropped_at = Some(now() - 60 * 60 * 24 * 91);
+        history.drop_removed_sources();
+        assert!(!history.sources.contains_key("lefigaro"));
+        assert!(history.sources.contains_key("lemonde"));
+    }
+
+    #[test]
+    fn test_should_flag_updated_articles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        let mut history = History::load(&path).expect("load history");
+        let mut status = article("1");
+        history.insert("status", &status, false);
+        history.read("status", &status);
+
+        // unchanged article
+        history.insert("status", &status, false);
+        assert!(!history.is_article_updated("status", &status));
+
+        // content changed: article stays read
+        status.summary.push_str("resolved");
+        history.insert("status", &status, false);
+        assert!(history.is_article_updated("status", &status));
+        assert!(history.is_article_read("status", &status));
+        history.save().expect("save history");
+        let mut history = History::load(&path).expect("load history");
+        assert!(history.is_article_updated("status", &status));
+        history.read("status", &status);
+        assert!(!history.is_article_updated("status", &status));
+
+        // update time changed: article is unread
+        status.updated = Some(Local::now());
+        history.insert("status", &status, true);
+        assert!(!history.is_article_updated("status", &status));
+        status.updated = status.updated.map(|x| x + chrono::Duration::hours(1));
+        history.insert("status", &status, true);
+        assert!(history.is_article_updated("status", &status));
+        assert!(!history.is_article_read("status", &status));
+        // but it is marked as read again when viewed
+        assert!(history.view("status", &status));
+        assert!(history.is_article_read("status", &status));
+        assert!(!history.is_article_updated("status", &status));
+
+        // articles never viewed are not flagged
+        let mut unread = article("2");
+        history.insert("status", &unread, false);
+        unread.summary.push_str("edited");
+        history.insert("status", &unread, false);
+        assert!(!history.is_article_updated("status", &unread));
+    }
+
+    #[test]
+    fn test_should_merge_exported_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.json");
+        let mut laptop = History::default();
+        laptop.read("figaro", &article("1"));
+        laptop.read("figaro", &article("2"));
+        laptop.insert("figaro", &article("3"), false);
+        laptop.read("lemonde", &article("1"));
+
+        let mut server = History::default();
+        server.read("figaro", &article("1"));
+        server.insert("figaro", &article("2"), false);
+        server.read("figaro", &article("3"));
+        server.read("nytimes", &article("1"));
+        // server read article 1 more recently, then marked it as unread
+        server.toggle("figaro", &article("1"));
+        server
+            .sources
+            .get_mut("figaro")
+            .unwrap()
+            .feed
+            .get_mut("1")
+            .unwrap()
+            .last_viewed = Some(now() + 60);
+        server.export(&path).expect("export history");
+
+        assert_eq!(laptop.import(&path).expect("import history"), 3);
+        assert!(!laptop.is_article_read("figaro", &article("1")));
+        // article 2 has been read on laptop only
+        assert!(laptop.is_article_read("figaro", &article("2")));
+        assert!(laptop.is_article_read("figaro", &article("3")));
+        assert!(laptop.is_article_read("lemonde", &article("1")));
+        assert!(laptop.is_article_read("nytimes", &article("1")));
+        // importing again changes nothing
+        assert_eq!(laptop.import(&path).expect("import history"), 0);
+        assert!(laptop.import(&dir.path().join("missing.json")).is_err());
+    }
+
+    fn article(id: &str) -> Article {
+        Article {
+            title: Some("title".to_string()),
+            url: "http://example.com".to_string(),
+            ..Article::mock(id)
+        }
+    }
+
+    #[test]
+    fn test_should_save_changes_to_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+
+        let mut history = History::load(&path).expect("load history");
+        history.read("figaro", &article("1"));
+        history.insert("figaro", &article("2"), false);
+        history.read("nytimes", &article("1"));
+        history.save().expect("save history");
+
+        // remove, rename and toggle, then save incrementally
+        history.filter_articles("figaro", &[&article("1")]);
+        history.set_source_identities(HashMap::from([(
+            String::from("nytimes"),
+            String::from("nyt"),
+        )]));
+        history.toggle("figaro", &article("1"));
+        history.save().expect("save history");
+
+        let history = History::load(&path).expect("load history");
+        let feed = &history.sources.get("figaro").unwrap().feed;
+        assert_eq!(feed.len(), 2);
+        assert!(feed.get("2").unwrap().is_dropped());
+        assert!(!history.is_article_read("figaro", &article("1")));
+        assert!(!history.sources.contains_key("nytimes"));
+        assert!(history.sources.contains_key("nyt"));
+    }
+
+    #[test]
+    fn test_should_migrate_json_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join(LEGACY_HISTORY_FILE);
+        let mut legacy = History::load(&json_path).expect("load history");
+        legacy.read("figaro", &article("1"));
+        legacy.insert("figaro", &article("2"), false);
+        legacy.save().expect("save history");
+        assert!(json_path.exists());
+
+        let history = History::load(&dir.path().join("history.db")).expect("load history");
+        assert!(history.is_article_read("figaro", &article("1")));
+        assert!(!history.is_article_read("figaro", &article("2")));
+        assert!(!json_path.exists());
+        assert!(dir.path().join("history.json.bak").exists());
+        // migrated history is stored in the database
+        let history = History::load(&dir.path().join("history.db")).expect("load history");
+        assert_eq!(history.sources.get("figaro").unwrap().feed.len(), 2);
+    }
+
+    #[test]
+    fn test_should_restore_corrupt_history_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+
+        let mut history = History::load(&path).expect("load history");
+        history.read("figaro", &article("1"));
+        history.save().expect("save history");
+        drop(history);
+        // loading writes the backup
+        let history = History::load(&path).expect("load history");
+        assert!(history.warning.is_none());
+        drop(history);
+        assert!(dir.path().join("history.db.bak").exists());
+
+        std::fs::write(
+            &path,
+            "this is not a database, but it's long enough to look like a header of one",
+        )
+        .unwrap();
+        let mut history = History::load(&path).expect("load history");
+        assert!(
+            history
+                .take_warning()
+                .unwrap()
+                .contains("restored from backup")
+        );
+        assert!(history.is_article_read("figaro", &article("1")));
+        assert!(dir.path().join("history.db.corrupt").exists());
+    }
+
+    #[test]
+    fn test_should_warn_about_corrupt_history_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::write(&path, "{\"sources\": {").unwrap();
+
+        let mut history = History::load(&path).expect("load history");
+        assert!(history.take_warning().unwrap().contains("no valid backup"));
+        assert!(history.sources.is_empty());
+        assert!(dir.path().join("history.json.corrupt").exists());
+    }
 }
@@ -0,0 +1,239 @@
+//! # Stats
+//!
+//! Reading statistics computed from the read history
+
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use unicode_truncate::{Alignment, UnicodeTruncateStr};
+
+use super::{History, now};
+use crate::helpers::fmt as fmt_helpers;
+
+/// Columns of the statistics table
+const TABLE_HEADER: [&str; 6] = [
+    "Source",
+    "Published",
+    "Read",
+    "Ratio",
+    "Median delay",
+    "Last read",
+];
+
+/// Reading statistics of a source
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceStats {
+    pub name: String,
+    /// Amount of articles published by the source, among the ones kept in history
+    pub published: usize,
+    /// Amount of published articles which have been read; articles marked as read along with their source aren't counted
+    pub read: usize,
+    /// Ratio between read and published articles, from 0 to 1
+    pub read_ratio: f64,
+    /// Median delay between the publication of an article and its read, in seconds
+    pub median_delay: Option<u64>,
+    /// Time an article of the source has been read last, as unix timestamp
+    pub last_read: Option<u64>,
+    /// Nothing has been read in the last `inactive_days` days
+    pub inactive: bool,
+}
+
+/// Reading statistics of all sources
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    /// Sources where nothing has been read for this amount of days are reported as inactive
+    pub inactive_days: u64,
+    pub sources: Vec<SourceStats>,
+}
+
+impl Stats {
+    /// Get the sources where nothing has been read in the last `inactive_days` days
+    pub fn inactive(&self) -> impl Iterator<Item = &SourceStats> {
+        self.sources.iter().filter(|x| x.inactive)
+    }
+
+    /// Format statistics as a table: the first line is the header, followed by a line for each source
+    pub fn table(&self) -> Vec<String> {
+        let now = Local::now();
+        let rows: Vec<[String; 6]> = self
+            .sources
+            .iter()
+            .map(|source| {
+                [
+                    source.name.clone(),
+                    source.published.to_string(),
+                    source.read.to_string(),
+                    format!("{:.0}%", source.read_ratio * 100.0),
+                    source
+                        .median_delay
+                        .map(|x| fmt_helpers::format_delay(Duration::from_secs(x)))
+                        .unwrap_or_else(|| String::from("-")),
+                    source
+                        .last_read
+                        .map(|x| {
+                            let date: DateTime<Local> =
+                                (SystemTime::UNIX_EPOCH + Duration::from_secs(x)).into();
+                            fmt_helpers::format_relative(date, now)
+                        })
+                        .unwrap_or_else(|| String::from("never")),
+                ]
+            })
+            .collect();
+        let header = TABLE_HEADER.map(String::from);
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                std::iter::once(&header)
+                    .chain(rows.iter())
+                    .map(|row| row[i].unicode_truncate(usize::MAX).1)
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        std::iter::once(&header)
+            .chain(rows.iter())
+            .map(|row| {
+                row.iter()
+                    .zip(widths.iter())
+                    .enumerate()
+                    .map(|(i, (cell, width))| {
+                        // the source name is aligned to the left, numbers to the right
+                        let align = if i == 0 {
+                            Alignment::Left
+                        } else {
+                            Alignment::Right
+                        };
+                        cell.unicode_pad(*width, align, false).to_string()
+                    })
+                    .collect::<Vec<String>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+impl History {
+    /// Compute the reading statistics of `sources`, sorted by name.
+    /// Sources where nothing has been read in the last `inactive_days` days are reported as inactive
+    pub fn stats<S: AsRef<str>>(&self, sources: &[S], inactive_days: u64) -> Stats {
+        let inactive_since = now().saturating_sub(inactive_days.saturating_mul(24 * 60 * 60));
+        let mut stats: Vec<SourceStats> = sources
+            .iter()
+            .map(|name| {
+                let articles: Vec<_> = self
+                    .sources
+                    .get(&self.source_key(name.as_ref()))
+                    .map(|x| x.feed.values().collect())
+                    .unwrap_or_default();
+                // articles marked as read in bulk haven't been read, so they would skew the delays
+                let read: Vec<_> = articles
+                    .iter()
+                    .filter(|x| x.is_read() && !x.dismissed)
+                    .collect();
+                let mut delays: Vec<u64> = read
+                    .iter()
+                    .filter_map(|x| x.last_viewed.map(|t| t.saturating_sub(x.timestamp)))
+                    .collect();
+                delays.sort_unstable();
+                let last_read = read.iter().filter_map(|x| x.last_viewed).max();
+
+                SourceStats {
+                    name: name.as_ref().to_string(),
+                    published: articles.len(),
+                    read: read.len(),
+                    read_ratio: if articles.is_empty() {
+                        0.0
+                    } else {
+                        read.len() as f64 / articles.len() as f64
+                    },
+                    median_delay: median(&delays),
+                    last_read,
+                    inactive: last_read.is_none_or(|x| x < inactive_since),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Stats {
+            inactive_days,
+            sources: stats,
+        }
+    }
+}
+
+/// Get the median of the sorted `values`
+fn median(values: &[u64]) -> Option<u64> {
+    match values.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((values[len / 2 - 1] + values[len / 2]) / 2),
+        len => Some(values[len / 2]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::feed::Article;
+
+    #[test]
+    fn should_compute_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3]), Some(3));
+        assert_eq!(median(&[1, 3, 10]), Some(3));
+        assert_eq!(median(&[1, 3, 5, 10]), Some(4));
+    }
+
+    #[test]
+    fn should_get_reading_stats() {
+        let mut history = History::default();
+        let published = now() - 7200;
+        for id in ["1", "2", "3", "4"] {
+            history.insert("figaro", &article(id, published), false);
+        }
+        history.read("figaro", &article("1", published));
+        history.read("figaro", &article("2", published));
+        history.insert("lemonde", &article("1", published), false);
+        // marking a source as read doesn't count as reading
+        history.read_source("figaro");
+        history.read_source("lemonde");
+
+        let stats = history.stats(&["lemonde", "figaro", "nytimes"], 30);
+        assert_eq!(stats.sources.len(), 3);
+        let figaro = &stats.sources[0];
+        assert_eq!(figaro.name.as_str(), "figaro");
+        assert_eq!(figaro.published, 4);
+        assert_eq!(figaro.read, 2);
+        assert_eq!(figaro.read_ratio, 0.5);
+        assert!(figaro.median_delay.is_some_and(|x| x >= 7200));
+        assert!(!figaro.inactive);
+        assert_eq!(
+            stats
+                .inactive()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["lemonde", "nytimes"]
+        );
+        let table = stats.table();
+        assert_eq!(table.len(), 4);
+        assert!(table[0].starts_with("Source "));
+        assert!(table[1].starts_with("figaro "));
+        assert!(table[3].ends_with("never"));
+        // the inactivity period doesn't overflow
+        assert!(!history.stats(&["figaro"], u64::MAX).sources[0].inactive);
+    }
+
+    fn article(id: &str, published: u64) -> Article {
+        let date: DateTime<Local> =
+            (SystemTime::UNIX_EPOCH + Duration::from_secs(published)).into();
+        Article {
+            date: Some(date),
+            ..Article::mock(id)
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! # Storage
+//!
+//! Storage backends for the read history
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+
+use super::{ArticleHistory, HistoryError, HistoryResult, SourceHistory};
+use crate::helpers::file as file_helpers;
+
+/// Statements which migrate the database schema; the schema version is the amount of applied migrations
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS articles (
+        source TEXT NOT NULL,
+        id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        last_viewed INTEGER,
+        unread INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (source, id)
+    ) WITHOUT ROWID;",
+    "ALTER TABLE articles ADD COLUMN dropped_at INTEGER;",
+    "ALTER TABLE articles ADD COLUMN fingerprint INTEGER;
+    ALTER TABLE articles ADD COLUMN updated_at INTEGER;
+    ALTER TABLE articles ADD COLUMN updated INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE articles ADD COLUMN dismissed INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// Migrations of the JSON history format; the format version is the amount of applied migrations.
+/// Files without a version header have version 0
+const JSON_MIGRATIONS: &[fn(&mut serde_json::Value)] = &[
+    // the version header has been introduced; the content is unchanged
+    |_| {},
+];
+
+/// Describes an entry of the history which has changed since the last save
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HistoryChange {
+    /// The whole history of a source has changed (e.g. it has been removed or renamed)
+    Source(String),
+    /// An article of a source has been inserted, updated or removed
+    Article(String, String),
+}
+
+/// A storage where the read history is persisted
+pub trait HistoryStorage: std::fmt::Debug {
+    /// Load the history of all sources
+    fn load(&mut self) -> HistoryResult<HashMap<String, SourceHistory>>;
+
+    /// Persist `changes`; `sources` contains the current state of the history
+    fn save(
+        &mut self,
+        sources: &HashMap<String, SourceHistory>,
+        changes: &[HistoryChange],
+    ) -> HistoryResult<()>;
+
+    /// Write a copy of the stored history to `path`
+    fn backup(&mut self, path: &Path) -> HistoryResult<()>;
+}
+
+/// Stores the whole history into a JSON file, which is rewritten on each save
+#[derive(Debug)]
+pub struct JsonStorage {
+    path: PathBuf,
+    /// Format version of the file when loaded; `None` if the file hasn't been loaded
+    version: Option<usize>,
+    /// Whether the file couldn't be parsed when loaded
+    unparsable: bool,
+}
+
+#[derive(Default, Deserialize)]
+struct JsonHistory {
+    sources: HashMap<String, SourceHistory>,
+}
+
+#[derive(Serialize)]
+struct JsonHistoryRef<'a> {
+    version: usize,
+    sources: &'a HashMap<String, SourceHistory>,
+}
+
+/// Version header of a JSON history
+#[derive(Deserialize)]
+struct JsonHeader {
+    #[serde(default)]
+    version: usize,
+}
+
+impl JsonStorage {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            version: None,
+            unparsable: false,
+        }
+    }
+
+    /// Read the JSON history file, migrating it to the current format version.
+    /// Fails if the file has been written by a newer version of tuifeed
+    fn read(&mut self) -> HistoryResult<serde_json::Value> {
+        let mut reader = File::open(&self.path)?;
+        let (mut history, header) =
+            match serde_json::from_reader(&mut reader).and_then(|history: serde_json::Value| {
+                JsonHeader::deserialize(&history).map(|header| (history, header))
+            }) {
+                Ok(history) => history,
+                Err(err) => {
+                    self.unparsable = true;
+                    return Err(err.into());
+                }
+            };
+        self.version = Some(header.version);
+        check_version(header.version, JSON_MIGRATIONS.len())?;
+        for migration in JSON_MIGRATIONS.iter().skip(header.version) {
+            migration(&mut history);
+        }
+
+        Ok(history)
+    }
+}
+
+impl HistoryStorage for JsonStorage {
+    fn load(&mut self) -> HistoryResult<HashMap<String, SourceHistory>> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let history = self.read()?;
+        match JsonHistory::deserialize(history) {
+            Ok(history) => Ok(history.sources),
+            Err(err) => {
+                self.unparsable = true;
+                Err(err.into())
+            }
+        }
+    }
+
+    fn save(
+        &mut self,
+        sources: &HashMap<String, SourceHistory>,
+        _changes: &[HistoryChange],
+    ) -> HistoryResult<()> {
+        // never overwrite a file which couldn't be parsed or which has been written by a newer version
+        if self.unparsable {
+            return Err(HistoryError::Unparsable(self.path.clone()));
+        }
+        if let Some(version) = self.version {
+            check_version(version, JSON_MIGRATIONS.len())?;
+        }
+        let data = serde_json::to_vec(&JsonHistoryRef {
+            version: JSON_MIGRATIONS.len(),
+            sources,
+        })?;
+        file_helpers::write_file_atomic(&self.path, data)?;
+
+        Ok(())
+    }
+
+    fn backup(&mut self, path: &Path) -> HistoryResult<()> {
+        if self.path.exists() {
+            file_helpers::write_file_atomic(path, std::fs::read(&self.path)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores the history into a SQLite database, where only the changed entries are written on save
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    /// Open the database at `path`, creating it if it doesn't exist.
+    /// Fails if the database is corrupt
+    pub fn open(path: &Path) -> HistoryResult<Self> {
+        let connection = Connection::open(path)?;
+        connection.pragma_query(None, "quick_check", |row| {
+            let result: String = row.get(0)?;
+            if result == "ok" {
+                Ok(())
+            } else {
+                Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+                    Some(result),
+                ))
+            }
+        })?;
+        let mut storage = Self { connection };
+        storage.migrate()?;
+
+        Ok(storage)
+    }
+
+    /// Apply the migrations which haven't been applied yet to the database schema.
+    /// Fails if the database has been written by a newer version of tuifeed
+    fn migrate(&mut self) -> HistoryResult<()> {
+        let version: usize = self
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        check_version(version, MIGRATIONS.len())?;
+        let transaction = self.connection.transaction()?;
+        for migration in MIGRATIONS.iter().skip(version) {
+            transaction.execute_batch(migration)?;
+        }
+        transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    /// Returns whether the database contains no articles
+    pub fn is_empty(&self) -> HistoryResult<bool> {
+        let article: Option<String> = self
+            .connection
+            .query_row("SELECT id FROM articles LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+
+        Ok(article.is_none())
+    }
+}
+
+impl HistoryStorage for SqliteStorage {
+    fn load(&mut self) -> HistoryResult<HashMap<String, SourceHistory>> {
+        let mut sources: HashMap<String, SourceHistory> = HashMap::new();
+        let mut statement = self.connection.prepare(
+            "SELECT source, id, timestamp, last_viewed, unread, dropped_at, fingerprint, updated_at, updated,
+            dismissed FROM articles",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                ArticleHistory {
+                    timestamp: row.get(2)?,
+                    last_viewed: row.get(3)?,
+                    unread: row.get(4)?,
+                    dropped_at: row.get(5)?,
+                    // SQLite integers are signed, so the fingerprint is stored with the same bits as an i64
+                    fingerprint: row.get::<_, Option<i64>>(6)?.map(|x| x as u64),
+                    updated_at: row.get(7)?,
+                    updated: row.get(8)?,
+                    dismissed: row.get(9)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (source, id, article) = row?;
+            sources.entry(source).or_default().feed.insert(id, article);
+        }
+
+        Ok(sources)
+    }
+
+    fn save(
+        &mut self,
+        sources: &HashMap<String, SourceHistory>,
+        changes: &[HistoryChange],
+    ) -> HistoryResult<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut upsert = transaction.prepare(
+                "INSERT OR REPLACE INTO articles
+                (source, id, timestamp, last_viewed, unread, dropped_at, fingerprint, updated_at, updated,
+                dismissed)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            let mut delete_article =
+                transaction.prepare("DELETE FROM articles WHERE source = ?1 AND id = ?2")?;
+            let mut delete_source =
+                transaction.prepare("DELETE FROM articles WHERE source = ?1")?;
+            for change in changes {
+                match change {
+                    HistoryChange::Source(source) => {
+                        delete_source.execute(params![source])?;
+                        let Some(history) = sources.get(source) else {
+                            continue;
+                        };
+                        for (id, article) in history.feed.iter() {
+                            upsert.execute(params![
+                                source,
+                                id,
+                                article.timestamp,
+                                article.last_viewed,
+                                article.unread,
+                                article.dropped_at,
+                                article.fingerprint.map(|x| x as i64),
+                                article.updated_at,
+                                article.updated,
+                                article.dismissed
+                            ])?;
+                        }
+                    }
+                    HistoryChange::Article(source, id) => {
+                        match sources.get(source).and_then(|x| x.feed.get(id)) {
+                            Some(article) => upsert.execute(params![
+                                source,
+                                id,
+                                article.timestamp,
+                                article.last_viewed,
+                                article.unread,
+                                article.dropped_at,
+                                article.fingerprint.map(|x| x as i64),
+                                article.updated_at,
+                                article.updated,
+                                article.dismissed
+                            ])?,
+                            None => delete_article.execute(params![source, id])?,
+                        };
+                    }
+                }
+            }
+        }
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn backup(&mut self, path: &Path) -> HistoryResult<()> {
+        // write the backup to a temporary file first, so the previous backup is kept if it fails
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = path.with_file_name(tmp_name);
+        let _ = std::fs::remove_file(&tmp);
+        self.connection
+            .execute("VACUUM INTO ?1", params![tmp.to_string_lossy()])?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+/// Check whether the history format `version` is supported, that is it isn't newer than the `supported` version
+fn check_version(version: usize, supported: usize) -> HistoryResult<()> {
+    if version > supported {
+        return Err(HistoryError::UnsupportedVersion {
+            found: version,
+            supported,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_load_every_json_format() {
+        for name in ["v0-legacy.json", "v0.json", "v1.json"] {
+            let sources = JsonStorage::new(&fixture(name))
+                .load()
+                .unwrap_or_else(|err| panic!("load {name}: {err}"));
+            let feed = &sources.get("figaro").unwrap().feed;
+            assert!(feed.get("1").unwrap().is_read(), "{name}");
+            assert!(!feed.get("2").unwrap().is_read(), "{name}");
+        }
+        for name in ["v0.json", "v1.json"] {
+            let sources = JsonStorage::new(&fixture(name)).load().unwrap();
+            let feed = &sources.get("figaro").unwrap().feed;
+            assert_eq!(
+                feed.get("1").unwrap().fingerprint,
+                Some(9316224022834808881),
+                "{name}"
+            );
+            assert!(feed.get("1").unwrap().updated, "{name}");
+            assert!(feed.get("2").unwrap().unread, "{name}");
+            assert!(feed.get("3").unwrap().is_dropped(), "{name}");
+        }
+    }
+
+    #[test]
+    fn should_write_json_format_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::copy(fixture("v0.json"), &path).unwrap();
+        let mut storage = JsonStorage::new(&path);
+        let sources = storage.load().unwrap();
+        storage.save(&sources, &[]).unwrap();
+
+        let header: JsonHeader = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(header.version, JSON_MIGRATIONS.len());
+        assert_eq!(storage.load().unwrap().get("figaro").unwrap().feed.len(), 3);
+    }
+
+    #[test]
+    fn should_migrate_every_database_schema() {
+        for name in ["v0.sql", "v2.sql", "v3.sql", "v4.sql"] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("history.db");
+            let script = std::fs::read_to_string(fixture(name)).unwrap();
+            Connection::open(&path)
+                .unwrap()
+                .execute_batch(&script)
+                .unwrap();
+
+            let mut storage =
+                SqliteStorage::open(&path).unwrap_or_else(|err| panic!("open {name}: {err}"));
+            let version: usize = storage
+                .connection
+                .pragma_query_value(None, "user_version", |row| row.get(0))
+                .unwrap();
+            assert_eq!(version, MIGRATIONS.len(), "{name}");
+            let sources = storage.load().unwrap();
+            let feed = &sources.get("figaro").unwrap().feed;
+            assert!(feed.get("1").unwrap().is_read(), "{name}");
+            assert!(!feed.get("2").unwrap().is_read(), "{name}");
+            // migrated databases can be written
+            storage
+                .save(&sources, &[HistoryChange::Source(String::from("figaro"))])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn should_refuse_newer_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::copy(fixture("newer.json"), &path).unwrap();
+        let mut storage = JsonStorage::new(&path);
+        assert!(matches!(
+            storage.load(),
+            Err(HistoryError::UnsupportedVersion { found: 999, .. })
+        ));
+        assert!(matches!(
+            storage.save(&HashMap::new(), &[]),
+            Err(HistoryError::UnsupportedVersion { .. })
+        ));
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            std::fs::read(fixture("newer.json")).unwrap()
+        );
+
+        let path = dir.path().join("history.db");
+        let script = std::fs::read_to_string(fixture("newer.sql")).unwrap();
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(&script)
+            .unwrap();
+        assert!(matches!(
+            SqliteStorage::open(&path),
+            Err(HistoryError::UnsupportedVersion { found: 999, .. })
+        ));
+    }
+
+    #[test]
+    fn should_not_overwrite_unparsable_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::write(&path, "{\"sources\":").unwrap();
+        let mut storage = JsonStorage::new(&path);
+        assert!(storage.load().is_err());
+        assert!(matches!(
+            storage.save(&HashMap::new(), &[]),
+            Err(HistoryError::Unparsable(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"sources\":");
+    }
+
+    /// Get the path of a history fixture
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/history")
+            .join(name)
+    }
+}
@@ -0,0 +1,201 @@
+//! # Sync
+//!
+//! Synchronization of the read history between machines through a shared directory
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{History, HistoryResult};
+
+/// Prefix of the history files written to the sync directory
+const SYNC_FILE_PREFIX: &str = "history-";
+/// File next to the history where the id of this machine is stored
+const MACHINE_ID_FILE: &str = "sync-id";
+
+/// Synchronizes the history through a directory shared between machines (e.g. a synced folder).
+/// Each machine exports its history to its own file in the directory and merges the files of the other machines,
+/// so that files are never written by more than one machine and conflicts are resolved article by article
+#[derive(Debug)]
+pub struct HistorySync {
+    dir: PathBuf,
+    /// File this machine exports its history to
+    path: PathBuf,
+    /// Modification time of the files of the other machines, when they were last merged
+    merged: HashMap<PathBuf, SystemTime>,
+    /// Whether the history has changed since it was last exported
+    changed: bool,
+    /// Files of the other machines which couldn't be merged during the last sync
+    warning: Option<String>,
+}
+
+impl HistorySync {
+    /// Sync history through `dir`, as the machine identified by `machine_id`
+    pub fn new(dir: &Path, machine_id: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            path: dir.join(format!("{SYNC_FILE_PREFIX}{machine_id}.json")),
+            merged: HashMap::new(),
+            changed: true,
+            warning: None,
+        }
+    }
+
+    /// Sync the history of `profile` through `dir`, as this machine.
+    /// The id of the machine is generated on first use and stored next to the history
+    pub fn open(dir: &Path, profile: Option<&str>) -> HistoryResult<Self> {
+        Ok(Self::new(dir, &machine_id(profile)?))
+    }
+
+    /// Get the sync directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Report that the history has changed, so it is exported on next sync
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// Take the warning about the files which couldn't be merged during the last sync, if any
+    pub fn take_warning(&mut self) -> Option<String> {
+        self.warning.take()
+    }
+
+    /// Merge the histories of the other machines which have changed since the last sync into `history`,
+    /// then export `history` if it has changed. Returns the amount of articles whose read state has changed.
+    /// Files which can't be merged (e.g. partially written or written by a newer version) are skipped until they change
+    /// again, and reported by [`HistorySync::take_warning`]
+    pub fn sync(&mut self, history: &mut History) -> HistoryResult<usize> {
+        if !self.dir.exists() {
+            std::fs::create_dir_all(&self.dir)?;
+        }
+        let mut changed = 0;
+        let mut skipped = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path == self.path || !is_sync_file(&path) {
+                continue;
+            }
+            // the file may be removed by the sync tool meanwhile
+            let Ok(modified) = std::fs::metadata(&path).and_then(|x| x.modified()) else {
+                continue;
+            };
+            if self.merged.get(&path) == Some(&modified) {
+                continue;
+            }
+            match history.import(&path) {
+                Ok(count) => changed += count,
+                Err(err) => skipped.push(format!("{}: {err}", path.display())),
+            }
+            self.merged.insert(path, modified);
+        }
+        if !skipped.is_empty() {
+            self.warning = Some(format!("Could not sync {}", skipped.join("; ")));
+        }
+        if changed > 0 || self.changed {
+            history.export(&self.path)?;
+            self.changed = false;
+        }
+
+        Ok(changed)
+    }
+}
+
+/// Returns whether `path` is a history file written by a machine
+fn is_sync_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| x.starts_with(SYNC_FILE_PREFIX) && x.ends_with(".json"))
+}
+
+/// Get the id of this machine for `profile`, generating it if it doesn't exist yet
+fn machine_id(profile: Option<&str>) -> HistoryResult<String> {
+    let path = History::default_path(profile)?.with_file_name(MACHINE_ID_FILE);
+    if path.exists() {
+        let id = std::fs::read_to_string(&path)?.trim().to_string();
+        if !id.is_empty() {
+            return Ok(id);
+        }
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_nanos())
+        .unwrap_or_default();
+    let id = format!(
+        "{:016x}",
+        (nanos as u64) ^ ((std::process::id() as u64) << 32)
+    );
+    std::fs::write(&path, &id)?;
+
+    Ok(id)
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::feed::Article;
+
+    #[test]
+    fn should_sync_history_through_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut laptop = History::default();
+        let mut laptop_sync = HistorySync::new(dir.path(), "laptop");
+        let mut desktop = History::default();
+        let mut desktop_sync = HistorySync::new(dir.path(), "desktop");
+
+        laptop.read("figaro", &article("1"));
+        assert_eq!(laptop_sync.sync(&mut laptop).unwrap(), 0);
+        assert!(dir.path().join("history-laptop.json").exists());
+
+        desktop.read("figaro", &article("2"));
+        assert_eq!(desktop_sync.sync(&mut desktop).unwrap(), 1);
+        assert!(desktop.is_article_read("figaro", &article("1")));
+        assert!(desktop.is_article_read("figaro", &article("2")));
+
+        assert_eq!(laptop_sync.sync(&mut laptop).unwrap(), 1);
+        assert!(laptop.is_article_read("figaro", &article("2")));
+        // unchanged files are not merged again
+        assert_eq!(laptop_sync.sync(&mut laptop).unwrap(), 0);
+    }
+
+    #[test]
+    fn should_skip_files_which_cant_be_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("history-broken.json"), "{\"sources\": {").unwrap();
+        let mut desktop = History::default();
+        desktop.read("figaro", &article("1"));
+        // articles which have never been read are not counted as synced
+        desktop.insert("figaro", &article("2"), false);
+        HistorySync::new(dir.path(), "desktop")
+            .sync(&mut desktop)
+            .unwrap();
+
+        let mut laptop = History::default();
+        let mut laptop_sync = HistorySync::new(dir.path(), "laptop");
+        assert_eq!(laptop_sync.sync(&mut laptop).unwrap(), 1);
+        assert!(laptop.is_article_read("figaro", &article("1")));
+        assert!(
+            laptop_sync
+                .take_warning()
+                .is_some_and(|x| x.contains("history-broken.json"))
+        );
+        // the broken file is not merged again until it changes
+        assert_eq!(laptop_sync.sync(&mut laptop).unwrap(), 0);
+        assert!(laptop_sync.take_warning().is_none());
+    }
+
+    #[test]
+    fn should_recognize_sync_files() {
+        assert!(is_sync_file(Path::new("/tmp/history-abc.json")));
+        assert!(!is_sync_file(Path::new("/tmp/history-abc.json.tmp")));
+        assert!(!is_sync_file(Path::new("/tmp/notes.json")));
+    }
+
+    fn article(id: &str) -> Article {
+        Article::mock(id)
+    }
+}
@@ -42,6 +42,21 @@ impl Kiosk {
         self.feed.insert(source.as_ref().to_string(), state);
     }
 
+    /// Remove a source from kiosk
+    pub fn remove_feed(&mut self, source: &str) -> Option<FeedState> {
+        self.feed.remove(source)
+    }
+
+    /// Rename a source, keeping its feed state
+    pub fn rename_feed(&mut self, source: &str, name: &str) {
+        if let Some(mut state) = self.feed.remove(source) {
+            if let FeedState::Success(feed) = &mut state {
+                feed.name = name.to_string();
+            }
+            self.feed.insert(name.to_string(), state);
+        }
+    }
+
     /// Returns the list of sources associated to their feed list
     pub fn get_state(&self) -> Vec<(String, FlatFeedState)> {
         self.feed
@@ -65,6 +80,14 @@ impl Kiosk {
         }
     }
 
+    /// Returns the amount of sources in the provided state
+    pub fn count(&self, state: FlatFeedState) -> usize {
+        self.feed
+            .values()
+            .filter(|x| FlatFeedState::from(*x) == state)
+            .count()
+    }
+
     /// Get sources in kiosk
     pub fn sources(&self) -> Vec<&String> {
         self.feed.keys().collect()
@@ -162,4 +185,42 @@ mod test {
             FeedState::Error(FeedError::Parse(String::from("parse error"))),
         );
     }
+
+    #[test]
+    fn should_rename_and_remove_feed() {
+        let mut kiosk = Kiosk::default();
+        kiosk.insert_feed(
+            "lefigaro",
+            FeedState::Success(Feed {
+                name: String::from("lefigaro"),
+                articles: Vec::default(),
+            }),
+        );
+        kiosk.rename_feed("lefigaro", "figaro");
+        assert!(kiosk.get_feed("lefigaro").is_none());
+        assert_eq!(kiosk.get_feed("figaro").unwrap().name, "figaro");
+        assert!(kiosk.remove_feed("figaro").is_some());
+        assert!(kiosk.sources().is_empty());
+    }
+
+    #[test]
+    fn should_count_sources_by_state() {
+        let mut kiosk = Kiosk::default();
+        kiosk.insert_feed(
+            "lefigaro",
+            FeedState::Success(Feed {
+                name: String::from("lefigaro"),
+                articles: Vec::default(),
+            }),
+        );
+        kiosk.insert_feed("lemonde", FeedState::Loading);
+        kiosk.insert_feed(
+            "nytimes",
+            FeedState::Error(FeedError::Parse(String::from("parse error"))),
+        );
+        kiosk.insert_feed("corriere", FeedState::Loading);
+        assert_eq!(kiosk.count(FlatFeedState::Success), 1);
+        assert_eq!(kiosk.count(FlatFeedState::Error), 1);
+        assert_eq!(kiosk.count(FlatFeedState::Loading), 2);
+    }
 }
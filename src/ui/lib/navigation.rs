@@ -0,0 +1,95 @@
+//! # Navigation
+//!
+//! Navigation through the unread articles of all the feeds
+
+/// Position of an article, as feed index and article index
+pub type ArticlePosition = (usize, usize);
+
+/// Find the position of the next unread article after `current`, moving through feeds in order.
+/// `feeds` contains, for each feed, whether its articles are read.
+/// The search wraps around and may return `current` itself if it's the only unread article.
+/// If `forward` is false, the previous unread article is searched instead
+pub fn find_unread(
+    feeds: &[Vec<bool>],
+    current: ArticlePosition,
+    forward: bool,
+) -> Option<ArticlePosition> {
+    let positions: Vec<ArticlePosition> = feeds
+        .iter()
+        .enumerate()
+        .flat_map(|(feed, articles)| (0..articles.len()).map(move |article| (feed, article)))
+        .collect();
+    if positions.is_empty() {
+        return None;
+    }
+    let len = positions.len();
+    let start = match positions.iter().position(|x| *x == current) {
+        Some(index) => index,
+        None => {
+            // current article doesn't exist: start right before the first article of the following feeds
+            let next = positions
+                .iter()
+                .position(|(feed, _)| *feed >= current.0)
+                .unwrap_or_default();
+            if forward {
+                (next + len - 1) % len
+            } else {
+                next
+            }
+        }
+    };
+    let is_unread = |(feed, article): ArticlePosition| !feeds[feed][article];
+    (1..=len)
+        .map(|offset| {
+            if forward {
+                (start + offset) % len
+            } else {
+                (start + len * 2 - offset) % len
+            }
+        })
+        .map(|index| positions[index])
+        .find(|position| is_unread(*position))
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_find_next_unread() {
+        let feeds = vec![vec![true, false, true], vec![], vec![true, false]];
+        assert_eq!(find_unread(&feeds, (0, 0), true), Some((0, 1)));
+        assert_eq!(find_unread(&feeds, (0, 1), true), Some((2, 1)));
+        // wrap around
+        assert_eq!(find_unread(&feeds, (2, 1), true), Some((0, 1)));
+        // empty feed
+        assert_eq!(find_unread(&feeds, (1, 0), true), Some((2, 1)));
+        assert_eq!(find_unread(&feeds, (1, 0), false), Some((0, 1)));
+    }
+
+    #[test]
+    fn should_find_previous_unread() {
+        let feeds = vec![vec![true, false, true], vec![], vec![true, false]];
+        assert_eq!(find_unread(&feeds, (2, 1), false), Some((0, 1)));
+        // wrap around
+        assert_eq!(find_unread(&feeds, (0, 1), false), Some((2, 1)));
+        assert_eq!(find_unread(&feeds, (0, 0), false), Some((2, 1)));
+    }
+
+    #[test]
+    fn should_not_find_unread_if_everything_is_read() {
+        let feeds = vec![vec![true, true], vec![true]];
+        assert_eq!(find_unread(&feeds, (0, 0), true), None);
+        assert_eq!(find_unread(&feeds, (1, 0), false), None);
+        assert_eq!(find_unread(&[], (0, 0), true), None);
+    }
+
+    #[test]
+    fn should_return_current_if_only_unread() {
+        let feeds = vec![vec![true, false]];
+        assert_eq!(find_unread(&feeds, (0, 1), true), Some((0, 1)));
+    }
+}
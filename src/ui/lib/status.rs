@@ -0,0 +1,58 @@
+//! # Status
+//!
+//! Transient messages shown in the status bar
+
+use std::time::{Duration, Instant};
+
+/// How long a status message is displayed
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A transient message for the status bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    text: String,
+    created_at: Instant,
+}
+
+impl StatusMessage {
+    pub fn new(text: impl ToString) -> Self {
+        Self {
+            text: text.to_string(),
+            created_at: Instant::now(),
+        }
+    }
+
+    /// Get message text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns whether the message should not be displayed anymore
+    pub fn is_expired(&self) -> bool {
+        self.created_at.elapsed() >= MESSAGE_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_create_status_message() {
+        let message = StatusMessage::new("Link opened");
+        assert_eq!(message.text(), "Link opened");
+        assert!(!message.is_expired());
+    }
+
+    #[test]
+    fn should_expire_status_message() {
+        let message = StatusMessage {
+            text: String::from("Link opened"),
+            created_at: Instant::now() - MESSAGE_TIMEOUT,
+        };
+        assert!(message.is_expired());
+    }
+}
@@ -1,51 +1,82 @@
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Local};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers};
-use tuirealm::props::{PropPayload, PropValue};
+use tuirealm::props::{Color, PropPayload, PropValue, TextSpan};
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::ratatui::widgets::Clear;
 use tuirealm::{
-    Application, AttrValue, Attribute, EventListenerCfg, NoUserEvent, Sub, SubClause,
-    SubEventClause,
+    Application, AttrValue, Attribute, EventListenerCfg, NoUserEvent, State, StateValue, Sub,
+    SubClause, SubEventClause,
 };
 
 use super::components::*;
-use super::lib::History;
+use super::lib::{DownloadStatus, History, MERGED_FEED_NAME};
 use super::{FlatFeedState, Id, Kiosk, Msg, Ui};
-use crate::config::Config;
+use crate::config::{Config, DateFormat};
 use crate::feed::{Article, Feed};
-use crate::helpers::{strings as str_helpers, ui as ui_helpers};
+use crate::helpers::{fmt as fmt_helpers, strings as str_helpers, ui as ui_helpers};
 
 static mut SUMMARY_WIDTH: usize = 0;
 const CROSSTERM_MAX_POLL: usize = 10;
+/// Default format for the article date in the article pane
+const ARTICLE_DATE_FORMAT: &str = "%A %d %B %Y, %H:%M";
+/// Default format for the article timestamp in the article list (RFC3339)
+const ARTICLE_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+/// Format for times in the status bar and in the error log
+const TIME_FORMAT: &str = "%H:%M:%S";
 
 /// Article view components
 struct ArticleView<'a> {
     authors: ArticleAuthors,
     date: ArticleDate,
+    enclosure: ArticleEnclosure,
     link: ArticleLink,
     summary: ArticleSummary<'a>,
     title: ArticleTitle,
 }
 
-impl From<&Article> for ArticleView<'_> {
-    fn from(article: &Article) -> Self {
+impl ArticleView<'_> {
+    fn new(article: &Article, config: &Config) -> Self {
         unsafe {
             Self {
                 authors: ArticleAuthors::new(article.authors.as_ref()),
-                date: ArticleDate::new(article.date),
+                date: Self::article_date(article, config),
+                enclosure: ArticleEnclosure::new(&article.enclosures),
                 link: ArticleLink::new(article.url.as_str()),
-                summary: ArticleSummary::new(article.summary.as_str(), SUMMARY_WIDTH),
+                summary: ArticleSummary::new(
+                    article.summary.as_str(),
+                    &article.links,
+                    SUMMARY_WIDTH,
+                ),
                 title: ArticleTitle::new(article.title.as_deref().unwrap_or("")),
             }
         }
     }
+
+    /// Get article date component, formatted according to configuration
+    fn article_date(article: &Article, config: &Config) -> ArticleDate {
+        ArticleDate::new(Self::fmt_article_date(article, config).as_deref())
+    }
+
+    /// Format article date according to configuration
+    fn fmt_article_date(article: &Article, config: &Config) -> Option<String> {
+        let format = config
+            .dates
+            .as_ref()
+            .and_then(|x| x.format.clone())
+            .unwrap_or_else(|| DateFormat::Format(ARTICLE_DATE_FORMAT.to_string()));
+        article
+            .date
+            .map(|date| format.format(date, &config.timezone()))
+    }
 }
 
 impl Ui {
     /// Initialize application
     pub(super) fn init_application(
         kiosk: &Kiosk,
+        merged_view: bool,
         ticks: Duration,
     ) -> Application<Id, Msg, NoUserEvent> {
         let mut app = Application::init(
@@ -54,8 +85,12 @@ impl Ui {
                 .poll_timeout(ticks),
         );
         assert!(
-            app.mount(Id::FeedList, Box::new(Self::get_feed_list(kiosk)), vec![])
-                .is_ok()
+            app.mount(
+                Id::FeedList,
+                Box::new(Self::get_feed_list(kiosk, merged_view)),
+                vec![]
+            )
+            .is_ok()
         );
         assert!(
             app.mount(
@@ -65,6 +100,10 @@ impl Ui {
             )
             .is_ok()
         );
+        assert!(
+            app.mount(Id::StatusBar, Box::new(StatusBar::new(&[])), vec![])
+                .is_ok()
+        );
         assert!(
             app.mount(
                 Id::GlobalListener,
@@ -81,10 +120,27 @@ impl Ui {
     pub(super) fn view(&mut self) {
         self.last_redraw = Instant::now();
         self.redraw = false;
+        self.update_status_bar();
         assert!(
             self.terminal
                 .raw_mut()
                 .draw(|f| {
+                    let error_log_height = if self.application.mounted(&Id::ErrorLog) {
+                        8
+                    } else {
+                        0
+                    };
+                    let main_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [
+                                Constraint::Min(0),                   // Body
+                                Constraint::Length(error_log_height), // Error log
+                                Constraint::Length(1),                // Status bar
+                            ]
+                            .as_ref(),
+                        )
+                        .split(f.area());
                     let chunks = Layout::default()
                         .direction(Direction::Horizontal)
                         .margin(1)
@@ -95,7 +151,7 @@ impl Ui {
                             ]
                             .as_ref(),
                         )
-                        .split(f.area());
+                        .split(main_chunks[0]);
 
                     // Render layout only if kiosk has been initialized
                     // -- list
@@ -117,6 +173,7 @@ impl Ui {
                                 Constraint::Length(1), // Authors + date
                                 Constraint::Min(6),    // Summary
                                 Constraint::Length(1), // Link
+                                Constraint::Length(1), // Enclosure
                             ]
                             .as_ref(),
                         )
@@ -141,11 +198,52 @@ impl Ui {
                         .view(&Id::ArticleSummary, f, article_chunks[2]);
                     self.application
                         .view(&Id::ArticleLink, f, article_chunks[3]);
+                    self.application
+                        .view(&Id::ArticleEnclosure, f, article_chunks[4]);
+                    // -- error log
+                    if self.application.mounted(&Id::ErrorLog) {
+                        self.application.view(&Id::ErrorLog, f, main_chunks[1]);
+                    }
+                    // -- status bar
+                    self.application.view(&Id::StatusBar, f, main_chunks[2]);
                     // -- popups
                     if self.application.mounted(&Id::QuitPopup) {
                         let popup = ui_helpers::draw_area_in(f.area(), 30, 10);
                         f.render_widget(Clear, popup);
                         self.application.view(&Id::QuitPopup, f, popup);
+                    } else if self.application.mounted(&Id::Stats) {
+                        let popup = ui_helpers::draw_area_in(f.area(), 80, 60);
+                        f.render_widget(Clear, popup);
+                        self.application.view(&Id::Stats, f, popup);
+                    } else if self.application.mounted(&Id::DownloadQueue) {
+                        let popup = ui_helpers::draw_area_in(f.area(), 80, 60);
+                        f.render_widget(Clear, popup);
+                        self.application.view(&Id::DownloadQueue, f, popup);
+                    } else if self.application.mounted(&Id::LinkPicker) {
+                        let popup = ui_helpers::draw_area_in(f.area(), 60, 50);
+                        f.render_widget(Clear, popup);
+                        self.application.view(&Id::LinkPicker, f, popup);
+                    } else if self.application.mounted(&Id::SourceFormName) {
+                        let popup = ui_helpers::draw_area_in(f.area(), 60, 30);
+                        f.render_widget(Clear, popup);
+                        let form_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints(
+                                [
+                                    Constraint::Length(3), // Name
+                                    Constraint::Length(3), // Url
+                                    Constraint::Min(0),
+                                ]
+                                .as_ref(),
+                            )
+                            .split(popup);
+                        self.application
+                            .view(&Id::SourceFormName, f, form_chunks[0]);
+                        self.application.view(&Id::SourceFormUrl, f, form_chunks[1]);
+                    } else if self.application.mounted(&Id::RemoveSourcePopup) {
+                        let popup = ui_helpers::draw_area_in(f.area(), 30, 10);
+                        f.render_widget(Clear, popup);
+                        self.application.view(&Id::RemoveSourcePopup, f, popup);
                     } else if self.application.mounted(&Id::ErrorPopup) {
                         let popup = ui_helpers::draw_area_in(f.area(), 50, 15);
                         f.render_widget(Clear, popup);
@@ -176,6 +274,257 @@ impl Ui {
         let _ = self.application.umount(&Id::ErrorPopup);
     }
 
+    /// Mount link picker for the provided links and give focus to it
+    pub(super) fn mount_link_picker(&mut self, links: &[String]) {
+        assert!(
+            self.application
+                .remount(
+                    Id::LinkPicker,
+                    Box::new(LinkPickerPopup::new(links)),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::LinkPicker).is_ok());
+    }
+
+    pub(super) fn umount_link_picker(&mut self) {
+        let _ = self.application.umount(&Id::LinkPicker);
+    }
+
+    /// Mount download queue and give focus to it
+    pub(super) fn mount_download_queue(&mut self) {
+        assert!(
+            self.application
+                .remount(
+                    Id::DownloadQueue,
+                    Box::new(DownloadQueuePopup::new(self.download_queue_rows())),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::DownloadQueue).is_ok());
+    }
+
+    pub(super) fn umount_download_queue(&mut self) {
+        let _ = self.application.umount(&Id::DownloadQueue);
+    }
+
+    /// Update download queue rows with the current downloads state
+    pub(super) fn update_download_queue(&mut self) {
+        let rows = self.download_queue_rows();
+        let _ = self.application.attr(
+            &Id::DownloadQueue,
+            Attribute::Content,
+            AttrValue::Table(rows),
+        );
+    }
+
+    /// Make download queue rows, showing state, progress and speed for each download
+    fn download_queue_rows(&self) -> Vec<Vec<TextSpan>> {
+        self.downloads
+            .iter()
+            .map(|download| {
+                let name = format!(
+                    "{} - {}",
+                    self.downloads.source_name(download),
+                    download.title
+                );
+                let row = match &download.status {
+                    DownloadStatus::Queued => TextSpan::from(format!("… {name} (queued)")),
+                    DownloadStatus::Running => {
+                        let progress = self.downloader.progress(&download.url).unwrap_or_default();
+                        let percentage = progress
+                            .total
+                            .filter(|x| *x > 0)
+                            .map(|total| format!("{:3}%", progress.downloaded * 100 / total))
+                            .unwrap_or_else(|| fmt_helpers::format_bytes(progress.downloaded));
+                        TextSpan::from(format!(
+                            "⬇ {percentage} {}/s {name}",
+                            fmt_helpers::format_bytes(progress.speed as u64)
+                        ))
+                        .fg(Color::LightYellow)
+                    }
+                    DownloadStatus::Completed => {
+                        TextSpan::from(format!("✔ {name}")).fg(Color::LightGreen)
+                    }
+                    DownloadStatus::Failed(err) => {
+                        TextSpan::from(format!("✘ {name}: {err}")).fg(Color::Red)
+                    }
+                };
+                vec![row]
+            })
+            .collect()
+    }
+
+    /// Mount error log panel and give focus to it
+    pub(super) fn mount_error_log(&mut self) {
+        assert!(
+            self.application
+                .remount(
+                    Id::ErrorLog,
+                    Box::new(ErrorLogPanel::new(self.error_log_rows())),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::ErrorLog).is_ok());
+    }
+
+    pub(super) fn umount_error_log(&mut self) {
+        let _ = self.application.umount(&Id::ErrorLog);
+    }
+
+    /// Update error log panel rows
+    pub(super) fn update_error_log(&mut self) {
+        let rows = self.error_log_rows();
+        let _ = self
+            .application
+            .attr(&Id::ErrorLog, Attribute::Content, AttrValue::Table(rows));
+    }
+
+    /// Make error log rows
+    fn error_log_rows(&self) -> Vec<Vec<TextSpan>> {
+        self.error_log
+            .iter()
+            .map(|entry| {
+                vec![
+                    TextSpan::from(format!("[{}] ", self.fmt_time(entry.timestamp))),
+                    TextSpan::from(format!("{}: ", entry.source)).bold(),
+                    TextSpan::from(entry.error.to_string()),
+                ]
+            })
+            .collect()
+    }
+
+    /// Mount reading statistics popup and give focus to it
+    pub(super) fn mount_stats(&mut self) {
+        assert!(
+            self.application
+                .remount(
+                    Id::Stats,
+                    Box::new(StatsPopup::new(self.stats_rows())),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::Stats).is_ok());
+    }
+
+    pub(super) fn umount_stats(&mut self) {
+        let _ = self.application.umount(&Id::Stats);
+    }
+
+    /// Make reading statistics rows: the table of sources, where inactive ones are highlighted, and the list of
+    /// inactive sources
+    fn stats_rows(&self) -> Vec<Vec<TextSpan>> {
+        let sources: Vec<&String> = self.config.sources.keys().collect();
+        let stats = self.history.stats(&sources, self.config.inactive_days());
+        let mut rows: Vec<Vec<TextSpan>> = stats
+            .table()
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| match i {
+                0 => vec![TextSpan::from(line).bold()],
+                i if stats.sources[i - 1].inactive => {
+                    vec![TextSpan::from(line).fg(Color::LightYellow)]
+                }
+                _ => vec![TextSpan::from(line)],
+            })
+            .collect();
+        let inactive: Vec<&str> = stats.inactive().map(|x| x.name.as_str()).collect();
+        if !inactive.is_empty() {
+            rows.push(vec![TextSpan::from("")]);
+            rows.push(vec![
+                TextSpan::from(format!(
+                    "Nothing read in the last {} days: ",
+                    stats.inactive_days
+                ))
+                .fg(Color::LightYellow),
+                TextSpan::from(inactive.join(", ")),
+            ]);
+        }
+        rows
+    }
+
+    /// Format article as plain text, to be read in an external program
+    pub(super) fn article_text(&self, article: &Article) -> String {
+        let mut text = String::new();
+        if let Some(title) = article.title.as_deref() {
+            text.push_str(&format!("{title}\n\n"));
+        }
+        if !article.authors.is_empty() {
+            text.push_str(&format!("{}\n", article.authors.join(", ")));
+        }
+        if let Some(date) = ArticleView::fmt_article_date(article, &self.config) {
+            text.push_str(&format!("{date}\n"));
+        }
+        text.push_str(&format!("{}\n\n", article.url));
+        text.push_str(article.summary.trim());
+        text.push('\n');
+        if !article.links.is_empty() {
+            text.push_str("\nLinks:\n");
+            for (i, link) in article.links.iter().enumerate() {
+                text.push_str(&format!("[{}] {link}\n", i + 1));
+            }
+        }
+        text
+    }
+
+    /// Format time in the configured timezone
+    fn fmt_time(&self, datetime: DateTime<Local>) -> String {
+        DateFormat::Format(TIME_FORMAT.to_string()).format(datetime, &self.config.timezone())
+    }
+
+    /// Update article date with the current time, in case it is relative
+    pub(super) fn update_article_date(&mut self) {
+        let Some(article) = self.get_selected_article() else {
+            return;
+        };
+        let date = ArticleView::article_date(article, &self.config);
+        assert!(
+            self.application
+                .remount(Id::ArticleDate, Box::new(date), vec![])
+                .is_ok()
+        );
+    }
+
+    /// Update status bar with sources state, unread articles, last refresh and the current message
+    pub(super) fn update_status_bar(&mut self) {
+        let loading = self.kiosk.count(FlatFeedState::Loading);
+        let failed = self.kiosk.count(FlatFeedState::Error);
+        let done = self.kiosk.count(FlatFeedState::Success);
+        let mut spans = vec![TextSpan::from(format!(" ✔ {done} "))];
+        if loading > 0 {
+            spans.push(TextSpan::from(format!("⟳ {loading} loading ")).fg(Color::LightYellow));
+        }
+        if failed > 0 {
+            spans.push(TextSpan::from(format!("✘ {failed} failed ")).fg(Color::Red));
+        }
+        if !self.error_log.is_empty() {
+            spans.push(
+                TextSpan::from(format!("⚠ {} errors (CTRL+E) ", self.error_log.len()))
+                    .fg(Color::Red)
+                    .bold(),
+            );
+        }
+        spans.push(TextSpan::from(format!("│ {} unread ", self.unread_count())).bold());
+        if let Some(last_refresh) = self.last_refresh {
+            spans.push(TextSpan::from(format!(
+                "│ refreshed at {} ",
+                self.fmt_time(last_refresh)
+            )));
+        }
+        if let Some(message) = self.status_message.as_ref() {
+            spans.push(TextSpan::from(format!("│ {}", message.text())).fg(Color::LightCyan));
+        }
+        assert!(
+            self.application
+                .remount(Id::StatusBar, Box::new(StatusBar::new(&spans)), vec![])
+                .is_ok()
+        );
+    }
+
     /// Mount quit popup
     pub(super) fn mount_quit_popup(&mut self) {
         assert!(
@@ -190,6 +539,132 @@ impl Ui {
         let _ = self.application.umount(&Id::QuitPopup);
     }
 
+    /// Mount the form to add or edit a source and give focus to the name field
+    pub(super) fn mount_source_form(&mut self, name: &str, url: &str) {
+        let title = if name.is_empty() {
+            "New source"
+        } else {
+            "Edit source"
+        };
+        assert!(
+            self.application
+                .remount(
+                    Id::SourceFormName,
+                    Box::new(SourceFormInput::new(
+                        &format!("{title} (<TAB> next field, <ENTER> save)"),
+                        name,
+                        "name"
+                    )),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(
+            self.application
+                .remount(
+                    Id::SourceFormUrl,
+                    Box::new(SourceFormInput::new("Url", url, "https://… or file:///…")),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::SourceFormName).is_ok());
+    }
+
+    pub(super) fn umount_source_form(&mut self) {
+        let _ = self.application.umount(&Id::SourceFormName);
+        let _ = self.application.umount(&Id::SourceFormUrl);
+        self.source_form = None;
+        assert!(self.application.active(&Id::FeedList).is_ok());
+    }
+
+    /// Move focus to the other field of the source form
+    pub(super) fn blur_source_form(&mut self) {
+        let next = if self.application.focus() == Some(&Id::SourceFormName) {
+            Id::SourceFormUrl
+        } else {
+            Id::SourceFormName
+        };
+        assert!(self.application.active(&next).is_ok());
+    }
+
+    /// Get the trimmed value of a field of the source form
+    pub(super) fn source_form_value(&self, id: &Id) -> String {
+        match self.application.state(id) {
+            Ok(State::One(StateValue::String(value))) => value.trim().to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Mount the popup to confirm the removal of a source
+    pub(super) fn mount_remove_source_popup(&mut self, name: &str) {
+        assert!(
+            self.application
+                .remount(
+                    Id::RemoveSourcePopup,
+                    Box::new(RemoveSourcePopup::new(name)),
+                    vec![]
+                )
+                .is_ok()
+        );
+        assert!(self.application.active(&Id::RemoveSourcePopup).is_ok());
+    }
+
+    pub(super) fn umount_remove_source_popup(&mut self) {
+        let _ = self.application.umount(&Id::RemoveSourcePopup);
+        assert!(self.application.active(&Id::FeedList).is_ok());
+    }
+
+    /// Remount the feed list after sources have changed, restoring the state of its items.
+    /// The source named `selected` is selected; if not provided, the current selection index is kept
+    pub(super) fn remount_feed_list(&mut self, selected: Option<&str>) {
+        let focus = self.application.focus() == Some(&Id::FeedList);
+        let current = match self.application.state(&Id::FeedList) {
+            Ok(State::One(StateValue::Usize(index))) => index,
+            _ => 0,
+        };
+        assert!(
+            self.application
+                .remount(
+                    Id::FeedList,
+                    Box::new(Self::get_feed_list(&self.kiosk, self.config.merged_view())),
+                    vec![]
+                )
+                .is_ok()
+        );
+        self.update_feed_list_items();
+        let sources = self.sorted_sources();
+        let index = selected
+            .and_then(|name| sources.iter().position(|x| x.as_str() == name))
+            .unwrap_or(current)
+            .min(sources.len().saturating_sub(1));
+        assert!(
+            self.application
+                .attr(
+                    &Id::FeedList,
+                    Attribute::Value,
+                    AttrValue::Payload(PropPayload::One(PropValue::Usize(index)))
+                )
+                .is_ok()
+        );
+        if focus {
+            assert!(self.application.active(&Id::FeedList).is_ok());
+        }
+    }
+
+    /// Remove all the entries from the article list
+    pub(super) fn clear_article_list(&mut self) {
+        assert!(
+            self.application
+                .remount(
+                    Id::ArticleList,
+                    Box::new(ArticleList::new(&[], None)),
+                    vec![]
+                )
+                .is_ok()
+        );
+    }
+
     /// Returns whether article list is empty
     pub(super) fn is_article_list_empty(&self) -> bool {
         self.application
@@ -236,9 +711,10 @@ impl Ui {
         let Some(source) = self.sorted_sources().first().cloned() else {
             return;
         };
-        let Some(feed) = self.kiosk.get_feed(source.as_str()) else {
+        let Some(feed) = self.get_feed(source.as_str()) else {
             return;
         };
+        let article = feed.articles().next().cloned();
 
         assert!(
             self.application
@@ -246,14 +722,15 @@ impl Ui {
                 .is_ok()
         );
         // Mount first article
-        if let Some(article) = feed.articles().next() {
+        if let Some(article) = article {
             let ArticleView {
                 authors,
                 date,
+                enclosure,
                 link,
                 summary,
                 title,
-            } = article.into();
+            } = ArticleView::new(&article, &self.config);
             assert!(
                 self.application
                     .remount(Id::ArticleAuthors, Box::new(authors), vec![])
@@ -264,6 +741,11 @@ impl Ui {
                     .remount(Id::ArticleDate, Box::new(date), vec![])
                     .is_ok()
             );
+            assert!(
+                self.application
+                    .remount(Id::ArticleEnclosure, Box::new(enclosure), vec![])
+                    .is_ok()
+            );
             assert!(
                 self.application
                     .remount(Id::ArticleLink, Box::new(link), vec![])
@@ -293,10 +775,36 @@ impl Ui {
     ) -> ArticleList {
         let articles: Vec<(String, bool)> = feed
             .articles()
-            .map(|article| {
+            .enumerate()
+            .map(|(index, article)| {
+                let sources = self.article_sources(feed, index);
+                let (source, source_article) = sources
+                    .first()
+                    .copied()
+                    .unwrap_or((feed.name.as_str(), article));
+                let mut prefix = String::new();
+                // mark articles changed since they have been read
+                if history.is_article_updated(source, source_article) {
+                    prefix.push_str("✎ ");
+                }
+                // mark downloaded episodes
+                if self.downloads.is_downloaded(source, &source_article.id) {
+                    prefix.push_str("↓ ");
+                }
+                // list the sources of merged articles
+                if self.is_merged_feed(&feed.name) {
+                    let names: Vec<&str> = sources.iter().map(|(name, _)| *name).collect();
+                    prefix.push_str(&format!("[{}] ", names.join(", ")));
+                }
+                // the title is truncated to fit with its prefix
+                let title = Self::fmt_article_title_in_list(
+                    config,
+                    article,
+                    max_title_len.saturating_sub(prefix.chars().count()),
+                );
                 (
-                    Self::fmt_article_title_in_list(config, article, max_title_len),
-                    history.is_article_read(&feed.name, article),
+                    format!("{prefix}{title}"),
+                    history.is_article_read(source, source_article),
                 )
             })
             .collect();
@@ -309,26 +817,34 @@ impl Ui {
         article: &Article,
         max_title_len: usize,
     ) -> String {
-        let article_title_config = config.article_title.unwrap_or_default();
+        let article_title_config = config.article_title.clone().unwrap_or_default();
         let mut title = String::new();
         // article date
-        if article_title_config.show_timestamp && article.date.is_some() {
-            title.push_str(format!("{} ", article.date.unwrap().to_rfc3339()).as_str());
+        if article_title_config.show_timestamp
+            && let Some(date) = article.date
+        {
+            let format = article_title_config
+                .timestamp_format
+                .unwrap_or_else(|| DateFormat::Format(ARTICLE_TIMESTAMP_FORMAT.to_string()));
+            title.push_str(format!("{} ", format.format(date, &config.timezone())).as_str());
         }
         if article_title_config.show_author && !article.authors.is_empty() {
             title.push_str(format!("({}) ", article.authors[0]).as_str());
         }
-        let max_title_len = max_title_len.saturating_sub(title.len());
+        let max_title_len = max_title_len.saturating_sub(title.len()).max(1);
         if let Some(article_title) = article.title.as_deref() {
             title.push_str(&str_helpers::elide_string_at(article_title, max_title_len))
         }
         title
     }
 
-    /// Get feed list component
-    fn get_feed_list(kiosk: &Kiosk) -> FeedList {
+    /// Get feed list component; if `merged_view` is enabled, the merged feed comes first
+    fn get_feed_list(kiosk: &Kiosk, merged_view: bool) -> FeedList {
         let mut sources = kiosk.get_state();
         sources.sort_by(|a, b| a.0.cmp(&b.0));
+        if merged_view {
+            sources.insert(0, (MERGED_FEED_NAME.to_string(), FlatFeedState::Loading));
+        }
         FeedList::new(sources)
     }
 
@@ -340,8 +856,51 @@ impl Ui {
                     code: Key::Esc,
                     modifiers: KeyModifiers::NONE,
                 }),
+                SubClause::Not(Box::new(Self::any_mounted(&[
+                    Id::DownloadQueue,
+                    Id::ErrorLog,
+                    Id::LinkPicker,
+                    Id::RemoveSourcePopup,
+                    Id::SourceFormName,
+                    Id::SourceFormUrl,
+                    Id::Stats,
+                ]))),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('d'),
+                    modifiers: KeyModifiers::CONTROL,
+                }),
+                SubClause::Always,
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('e'),
+                    modifiers: KeyModifiers::CONTROL,
+                }),
                 SubClause::Always,
             ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('l'),
+                    modifiers: KeyModifiers::CONTROL,
+                }),
+                SubClause::Always,
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('n'),
+                    modifiers: KeyModifiers::NONE,
+                }),
+                Self::no_popup(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('N'),
+                    modifiers: KeyModifiers::SHIFT,
+                }),
+                Self::no_popup(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(KeyEvent {
                     code: Key::Char('r'),
@@ -354,6 +913,13 @@ impl Ui {
                     code: Key::Char('r'),
                     modifiers: KeyModifiers::NONE,
                 }),
+                Self::not_typing(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                }),
                 SubClause::Always,
             ),
             Sub::new(
@@ -368,11 +934,49 @@ impl Ui {
                     code: Key::Char('v'),
                     modifiers: KeyModifiers::NONE,
                 }),
-                SubClause::Always,
+                Self::not_typing(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(KeyEvent {
+                    code: Key::Char('V'),
+                    modifiers: KeyModifiers::SHIFT,
+                }),
+                Self::not_typing(),
             ),
         ]
     }
 
+    /// Make a clause which is satisfied unless the user is typing into an input field
+    fn not_typing() -> SubClause<Id> {
+        SubClause::Not(Box::new(Self::any_mounted(&[
+            Id::SourceFormName,
+            Id::SourceFormUrl,
+        ])))
+    }
+
+    /// Make a clause which is satisfied unless a popup or an input field is mounted
+    fn no_popup() -> SubClause<Id> {
+        SubClause::Not(Box::new(Self::any_mounted(&[
+            Id::DownloadQueue,
+            Id::ErrorLog,
+            Id::ErrorPopup,
+            Id::LinkPicker,
+            Id::QuitPopup,
+            Id::RemoveSourcePopup,
+            Id::SourceFormName,
+            Id::SourceFormUrl,
+            Id::Stats,
+        ])))
+    }
+
+    /// Make a clause which is satisfied if any of the provided components is mounted
+    fn any_mounted(ids: &[Id]) -> SubClause<Id> {
+        ids.iter()
+            .map(|id| SubClause::IsMounted(id.clone()))
+            .reduce(|acc, clause| SubClause::Or(Box::new(acc), Box::new(clause)))
+            .unwrap_or(SubClause::Always)
+    }
+
     /// Get terminal width. If it fails to collect width, returns 65535
     fn terminal_width(&self) -> usize {
         self.terminal
@@ -388,41 +992,47 @@ impl Ui {
 
     /// Update article into view by index
     pub(super) fn update_article(&mut self, article: usize) {
-        if let Some(feed) = self.get_selected_feed() {
-            if let Some(article) = feed.articles().nth(article) {
-                let ArticleView {
-                    authors,
-                    date,
-                    link,
-                    summary,
-                    title,
-                } = article.into();
-                assert!(
-                    self.application
-                        .remount(Id::ArticleAuthors, Box::new(authors), vec![])
-                        .is_ok()
-                );
-                assert!(
-                    self.application
-                        .remount(Id::ArticleDate, Box::new(date), vec![])
-                        .is_ok()
-                );
-                assert!(
-                    self.application
-                        .remount(Id::ArticleLink, Box::new(link), vec![])
-                        .is_ok()
-                );
-                assert!(
-                    self.application
-                        .remount(Id::ArticleSummary, Box::new(summary), vec![])
-                        .is_ok()
-                );
-                assert!(
-                    self.application
-                        .remount(Id::ArticleTitle, Box::new(title), vec![])
-                        .is_ok()
-                );
-            }
+        if let Some(feed) = self.get_selected_feed()
+            && let Some(article) = feed.articles().nth(article)
+        {
+            let ArticleView {
+                authors,
+                date,
+                enclosure,
+                link,
+                summary,
+                title,
+            } = ArticleView::new(article, &self.config);
+            assert!(
+                self.application
+                    .remount(Id::ArticleAuthors, Box::new(authors), vec![])
+                    .is_ok()
+            );
+            assert!(
+                self.application
+                    .remount(Id::ArticleDate, Box::new(date), vec![])
+                    .is_ok()
+            );
+            assert!(
+                self.application
+                    .remount(Id::ArticleEnclosure, Box::new(enclosure), vec![])
+                    .is_ok()
+            );
+            assert!(
+                self.application
+                    .remount(Id::ArticleLink, Box::new(link), vec![])
+                    .is_ok()
+            );
+            assert!(
+                self.application
+                    .remount(Id::ArticleSummary, Box::new(summary), vec![])
+                    .is_ok()
+            );
+            assert!(
+                self.application
+                    .remount(Id::ArticleTitle, Box::new(title), vec![])
+                    .is_ok()
+            );
         }
     }
 }
@@ -0,0 +1,6 @@
+CREATE TABLE articles (
+    source TEXT NOT NULL,
+    id TEXT NOT NULL,
+    PRIMARY KEY (source, id)
+) WITHOUT ROWID;
+PRAGMA user_version = 999;
@@ -0,0 +1,16 @@
+-- History database written before the schema was versioned: user_version is 0 and there is no dropped_at column
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE articles (
+                source TEXT NOT NULL,
+                id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                last_viewed INTEGER,
+                unread INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (source, id)
+            ) WITHOUT ROWID;
+INSERT INTO articles VALUES('figaro','1',1792369090,1792369090,0);
+INSERT INTO articles VALUES('figaro','2',1792369090,NULL,1);
+INSERT INTO articles VALUES('figaro','3',1792369090,NULL,0);
+COMMIT;
+PRAGMA user_version = 0;
@@ -0,0 +1,16 @@
+-- History database with the dropped_at column (schema version 2)
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE articles (
+        source TEXT NOT NULL,
+        id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        last_viewed INTEGER,
+        unread INTEGER NOT NULL DEFAULT 0, dropped_at INTEGER,
+        PRIMARY KEY (source, id)
+    ) WITHOUT ROWID;
+INSERT INTO articles VALUES('figaro','1',1792369109,1792369109,0,NULL);
+INSERT INTO articles VALUES('figaro','2',1792369109,NULL,1,NULL);
+INSERT INTO articles VALUES('figaro','3',1792369109,1792369109,0,1792369109);
+COMMIT;
+PRAGMA user_version = 2;
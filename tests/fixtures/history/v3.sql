@@ -0,0 +1,16 @@
+-- History database with the update tracking columns (schema version 3)
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE articles (
+        source TEXT NOT NULL,
+        id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        last_viewed INTEGER,
+        unread INTEGER NOT NULL DEFAULT 0, dropped_at INTEGER, fingerprint INTEGER, updated_at INTEGER, updated INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (source, id)
+    ) WITHOUT ROWID;
+INSERT INTO articles VALUES('figaro','1',1792369127,1792369127,0,NULL,-9130520050874742735,NULL,1);
+INSERT INTO articles VALUES('figaro','2',1792369127,NULL,1,NULL,-9129563475758388390,NULL,0);
+INSERT INTO articles VALUES('figaro','3',1792369127,1792369127,0,1792369127,-9129563475758388390,NULL,0);
+COMMIT;
+PRAGMA user_version = 3;